@@ -1,4 +1,6 @@
 /// A simple in-memory IDP
+use std::time::Duration;
+
 use forbidden::forms::UserPassForm;
 use forbidden::prelude::*;
 use forbidden::users::UserPass;
@@ -18,19 +20,23 @@ impl PasswordIsSafe for ByPass {
 
 struct TestProvider {
     users: [UserPass; 2],
+    tokens: MemTokenStore,
 }
 
 impl TestProvider {
     pub fn new() -> Self {
-        let p = Password::hash(TEST_PWD, ByPass {}).unwrap();
+        let p = Password::hash_check(TEST_PWD, ByPass {}).unwrap();
         let u1 = UserPass::new(USER_1, p.clone());
         let u2 = UserPass::new(USER_2, p);
 
-        TestProvider { users: [u1, u2] }
+        TestProvider {
+            users: [u1, u2],
+            tokens: MemTokenStore::new(),
+        }
     }
 }
 
-/// Log in with credential [UserPassForm] and return token as [String]
+/// Log in with credential [UserPassForm] and return an opaque [Token]
 impl IdentityProvider<UserPassForm> for TestProvider {
     type Identity = UserPass;
 
@@ -38,16 +44,37 @@ impl IdentityProvider<UserPassForm> for TestProvider {
         Ok(self.users.iter().find(|x| x.identity_id() == id).cloned())
     }
 
-    fn find_by_token(&self, _token: &Token) -> ResultAuth<Option<Self::Identity>> {
-        todo!()
+    fn find_by_token(&self, token: &Token) -> ResultAuth<Option<Self::Identity>> {
+        let now = chrono::Utc::now().fixed_offset();
+        match self.tokens.lookup(token, now) {
+            Some(id) => self.find(&id),
+            None => Ok(None),
+        }
     }
 
-    fn logout(&self, _token: &Token) -> ResultAuth<bool> {
-        Ok(true)
+    fn logout(&self, token: &Token) -> ResultAuth<bool> {
+        Ok(self.tokens.revoke(token))
     }
 }
 
-impl IdentityProviderUserPwd for TestProvider {}
+impl IdentityProviderUserPwd for TestProvider {
+    fn login(&self, identity: &UserPassForm) -> ResultAuth<Token> {
+        self.verify_password(identity)?;
+        let now = chrono::Utc::now().fixed_offset();
+        Ok(self.tokens.issue(&identity.username, now, Duration::from_secs(3600)))
+    }
+
+    fn verify_password(&self, credentials: &UserPassForm) -> ResultAuth<Token> {
+        if let Some(user) = self.find(&credentials.username)? {
+            user.pwd.validate_password(&credentials.pwd)?;
+            Ok(credentials.into())
+        } else {
+            Err(AuthError::UserNotFound {
+                named: credentials.username.clone(),
+            })
+        }
+    }
+}
 
 fn main() -> ResultAuth<()> {
     let idp = TestProvider::new();
@@ -57,7 +84,11 @@ fn main() -> ResultAuth<()> {
     let mut form = UserPassForm::new(USER_1, "wrong");
     assert!(idp.login(&form).is_err());
     form.pwd = TEST_PWD.into();
-    assert!(idp.login(&form).is_ok());
+    let token = idp.login(&form)?;
+
+    assert!(idp.find_by_token(&token)?.is_some());
+    assert!(idp.logout(&token)?);
+    assert!(idp.find_by_token(&token)?.is_none());
 
     Ok(())
 }
@@ -0,0 +1,89 @@
+//! Separates *who authenticated* from *which principal the system acts as*.
+//!
+//! [AuthCId] is the authentication id: the credential that was actually verified (form-dependent,
+//! e.g. a username or email). [AuthZId] is the authorization id: an internal, stable principal
+//! with a main uid, an optional sub-uid, and a realm. One authenticated [AuthCId] can map to
+//! several [AuthZId]s (e.g. a default account plus a broader `+admin` sub-account), letting a
+//! single login switch permission scopes without re-authenticating.
+
+use crate::errors::{AuthError, ResultAuth};
+use crate::identity::REALM_DEFAULT;
+
+/// The identity that was verified by a credential check (a username, an email, ...).
+#[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct AuthCId(pub String);
+
+/// A stable, internal authorization principal: a main uid, an optional sub-uid, and a realm.
+#[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct AuthZId {
+    pub uid: String,
+    pub subuid: Option<String>,
+    pub realm: String,
+}
+
+impl AuthZId {
+    pub fn new(uid: &str, subuid: Option<&str>, realm: &str) -> Self {
+        AuthZId {
+            uid: uid.into(),
+            subuid: subuid.map(String::from),
+            realm: realm.into(),
+        }
+    }
+
+    /// The default (non-sub) authorization id for `uid` in [REALM_DEFAULT].
+    pub fn default_for(uid: &str) -> Self {
+        Self::new(uid, None, REALM_DEFAULT)
+    }
+}
+
+/// Resolves an authenticated [AuthCId] to the [AuthZId] principals it may act as.
+pub trait AuthZResolver {
+    /// Every authorization id this authenticated identity may select, e.g. its default account
+    /// plus any sub-accounts.
+    fn authorization_ids(&self, auth: &AuthCId) -> ResultAuth<Vec<AuthZId>>;
+
+    /// Confirm `selected` is one of `auth`'s available authorization ids.
+    fn select(&self, auth: &AuthCId, selected: &AuthZId) -> ResultAuth<AuthZId> {
+        let available = self.authorization_ids(auth)?;
+
+        if available.contains(selected) {
+            Ok(selected.clone())
+        } else {
+            Err(AuthError::IdentityNotFound {
+                named: selected.uid.clone(),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct OneSubAccount;
+
+    impl AuthZResolver for OneSubAccount {
+        fn authorization_ids(&self, auth: &AuthCId) -> ResultAuth<Vec<AuthZId>> {
+            Ok(vec![
+                AuthZId::default_for(&auth.0),
+                AuthZId::new(&auth.0, Some("admin"), REALM_DEFAULT),
+            ])
+        }
+    }
+
+    #[test]
+    fn selects_among_available_authz_ids() {
+        let resolver = OneSubAccount;
+        let auth = AuthCId("alice".into());
+
+        assert!(resolver
+            .select(&auth, &AuthZId::default_for("alice"))
+            .is_ok());
+        assert!(resolver
+            .select(&auth, &AuthZId::new("alice", Some("admin"), REALM_DEFAULT))
+            .is_ok());
+        assert!(resolver
+            .select(&auth, &AuthZId::new("alice", Some("superuser"), REALM_DEFAULT))
+            .is_err());
+    }
+}
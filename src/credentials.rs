@@ -24,18 +24,61 @@ pub struct CredentialEmail {
     pub email: String,
 }
 
+impl CredentialEmail {
+    pub fn new(email: &str) -> Self {
+        Self { email: email.into() }
+    }
+}
+
 /// Represent an opaque Token given, probably, by a external auth system.
 #[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
 pub struct CredentialToken {
     pub data: String,
 }
 
+impl CredentialToken {
+    pub fn new(data: &str) -> Self {
+        Self { data: data.into() }
+    }
+}
+
+impl From<CredentialToken> for Credential {
+    fn from(x: CredentialToken) -> Self {
+        Credential::Token(x)
+    }
+}
+
+/// Represent a [crate::totp::Totp] second-factor code submitted alongside a primary credential.
+#[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct CredentialTotp {
+    pub code: String,
+}
+
+/// Represent a client-certificate or public-key credential, identified by the fingerprint of
+/// the certificate (e.g. a hex-encoded SHA-256 of its DER encoding).
+#[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct CredentialCertificate {
+    pub fingerprint: String,
+    pub owner: String,
+}
+
+impl CredentialCertificate {
+    pub fn new(fingerprint: &str, owner: &str) -> Self {
+        Self {
+            fingerprint: fingerprint.into(),
+            owner: owner.into(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
 pub enum Credential {
     Anon(String),
     User(CredentialUser),
     UserEmail(CredentialEmail),
     Token(CredentialToken),
+    Totp(CredentialTotp),
+    Certificate(CredentialCertificate),
 }
 
 impl From<CredentialUser> for Credential {
@@ -49,3 +92,15 @@ impl From<CredentialEmail> for Credential {
         Credential::UserEmail(x)
     }
 }
+
+impl From<CredentialTotp> for Credential {
+    fn from(x: CredentialTotp) -> Self {
+        Credential::Totp(x)
+    }
+}
+
+impl From<CredentialCertificate> for Credential {
+    fn from(x: CredentialCertificate) -> Self {
+        Credential::Certificate(x)
+    }
+}
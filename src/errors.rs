@@ -2,6 +2,24 @@ use std::error::Error;
 
 pub type ResultAuth<T> = Result<T, AuthError>;
 pub type ResultPwd<T> = Result<T, PasswordError>;
+pub type ResultStore<T> = Result<T, StoreError>;
+
+/// Errors raised by a [crate::store::CredentialStore].
+#[derive(Debug)]
+pub enum StoreError {
+    /// No record was found for the given identity id.
+    CredentialNotFound { id: String },
+    /// The persisted store is unreadable or violates an invariant (e.g. a duplicate id, or an
+    /// unparseable format version).
+    StoreCorrupt { reason: String },
+    Io(std::io::Error),
+}
+
+impl From<std::io::Error> for StoreError {
+    fn from(err: std::io::Error) -> Self {
+        StoreError::Io(err)
+    }
+}
 
 #[derive(Debug)]
 pub enum PasswordError {
@@ -10,6 +28,10 @@ pub enum PasswordError {
     MaximumPasswordLength { provided: usize },
     InvalidPasswordAlgo { provided: String },
     HashError(password_hash::Error),
+    /// The password was found in a breach corpus (see [crate::pwned]), with this many sightings.
+    CompromisedPassword { occurrences: u64 },
+    /// The requested hashing cost parameters were rejected by the underlying algorithm.
+    InvalidParams { reason: String },
 }
 
 impl From<password_hash::Error> for PasswordError {
@@ -26,6 +48,16 @@ pub enum AuthError {
     EmailNotFound { email: String },
     UserNotFound { named: String },
     TokenNotFound { token: String },
+    /// The submitted TOTP code did not match, or was already consumed.
+    InvalidTotpCode,
+    /// The identity has no second factor configured.
+    TotpNotConfigured,
+    /// The failure count for this identity has crossed the provider's lockout threshold.
+    AccountLocked { identity_id: String },
+    /// The identity is flagged [crate::users::UserFlag::DISABLED].
+    AccountDisabled { identity_id: String },
+    /// The invitation does not exist, already expired, or was already redeemed.
+    InvalidInvitation,
 }
 
 impl From<PasswordError> for AuthError {
@@ -37,3 +37,21 @@ impl EmailPassForm {
 pub struct TokenForm {
     pub data: String,
 }
+
+/// Represent a username/password form with a required TOTP second-factor code.
+#[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct UserPassTotpForm {
+    pub username: String,
+    pub pwd: String,
+    pub totp_code: String,
+}
+
+impl UserPassTotpForm {
+    pub fn new(username: &str, pwd: &str, totp_code: &str) -> Self {
+        Self {
+            username: username.into(),
+            pwd: pwd.into(),
+            totp_code: totp_code.into(),
+        }
+    }
+}
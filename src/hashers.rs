@@ -0,0 +1,246 @@
+//! Pluggable password-hashing backends, dispatched by the algorithm id embedded in a stored PHC
+//! (or PHC-like, for bcrypt) string, so hashes produced by different algorithms -- or different
+//! cost parameters of the same algorithm -- can coexist in the same store and be migrated
+//! between transparently.
+//!
+//! This is a lower-level, swappable-backend counterpart to [crate::password::Password]: where
+//! `Password` pins itself to one algorithm at construction time, a [HasherRegistry] can verify
+//! against whichever backend a given stored hash names, and flag it for rehashing under the
+//! registry's current default.
+
+use std::collections::HashMap;
+
+use password_hash::PasswordHash;
+
+use crate::errors::{PasswordError, ResultPwd};
+use crate::password::{Argon2Params, Password, PasswordIsSafe, PasswordPolicy, ScryptParams};
+
+/// A swappable password-hashing backend producing/consuming PHC (or PHC-like) strings.
+pub trait Hasher {
+    /// The algorithm id this backend reads/writes (e.g. `argon2id`, `scrypt`, `bcrypt`).
+    fn algorithm_id(&self) -> &'static str;
+    /// Hash `raw`, producing a PHC (or PHC-like) string.
+    fn hash(&self, raw: &str) -> ResultPwd<String>;
+    /// Verify `raw` against a stored hash produced by this backend.
+    fn verify(&self, raw: &str, phc: &str) -> ResultPwd<bool>;
+    /// Does `phc` use a weaker cost than this backend's currently configured parameters?
+    fn needs_rehash(&self, phc: &str) -> bool;
+}
+
+/// A [Hasher] backed by [crate::password::Password]'s Argon2 path.
+pub struct Argon2Hasher(pub Argon2Params);
+
+impl Hasher for Argon2Hasher {
+    fn algorithm_id(&self) -> &'static str {
+        "argon2id"
+    }
+
+    fn hash(&self, raw: &str) -> ResultPwd<String> {
+        Ok(Password::hash_argon_with(raw, self.0, AcceptAny)?.phc)
+    }
+
+    fn verify(&self, raw: &str, phc: &str) -> ResultPwd<bool> {
+        Ok(Password::new(phc)?.validate_password(raw).is_ok())
+    }
+
+    fn needs_rehash(&self, phc: &str) -> bool {
+        Password::new(phc)
+            .map(|p| p.needs_rehash(&PasswordPolicy::Argon2(self.0)))
+            .unwrap_or(true)
+    }
+}
+
+/// A [Hasher] backed by [crate::password::Password]'s Scrypt path.
+pub struct ScryptHasher(pub ScryptParams);
+
+impl Hasher for ScryptHasher {
+    fn algorithm_id(&self) -> &'static str {
+        "scrypt"
+    }
+
+    fn hash(&self, raw: &str) -> ResultPwd<String> {
+        Ok(Password::hash_scrypt_with(raw, self.0, AcceptAny)?.phc)
+    }
+
+    fn verify(&self, raw: &str, phc: &str) -> ResultPwd<bool> {
+        Ok(Password::new(phc)?.validate_password(raw).is_ok())
+    }
+
+    fn needs_rehash(&self, phc: &str) -> bool {
+        Password::new(phc)
+            .map(|p| p.needs_rehash(&PasswordPolicy::Scrypt(self.0)))
+            .unwrap_or(true)
+    }
+}
+
+/// A [Hasher] backed by the `bcrypt` algorithm, whose `$2b$<cost>$...` strings are PHC-like but
+/// not spec-compliant PHC, so they are handled outside [password_hash::PasswordHash] entirely.
+pub struct BcryptHasher {
+    pub cost: u32,
+}
+
+impl Hasher for BcryptHasher {
+    fn algorithm_id(&self) -> &'static str {
+        "bcrypt"
+    }
+
+    fn hash(&self, raw: &str) -> ResultPwd<String> {
+        bcrypt::hash(raw, self.cost).map_err(|_| PasswordError::InvalidParams {
+            reason: format!("invalid bcrypt cost {}", self.cost),
+        })
+    }
+
+    fn verify(&self, raw: &str, phc: &str) -> ResultPwd<bool> {
+        bcrypt::verify(raw, phc).map_err(|_| PasswordError::InvalidPassword)
+    }
+
+    fn needs_rehash(&self, phc: &str) -> bool {
+        bcrypt_cost(phc).map(|cost| cost < self.cost).unwrap_or(true)
+    }
+}
+
+fn bcrypt_cost(phc: &str) -> Option<u32> {
+    phc.split('$').nth(2)?.parse().ok()
+}
+
+/// An internal no-op safety checker: registry backends hash already-accepted raw passwords
+/// (e.g. during a rehash), so re-running a [PasswordIsSafe] check here would be redundant.
+struct AcceptAny;
+
+impl PasswordIsSafe for AcceptAny {
+    fn is_safe(&self, _raw: &str) -> ResultPwd<()> {
+        Ok(())
+    }
+}
+
+/// Sniff the algorithm id a stored hash was produced with, without fully parsing it.
+fn sniff_algorithm_id(phc: &str) -> ResultPwd<String> {
+    if phc.starts_with("$2a$") || phc.starts_with("$2b$") || phc.starts_with("$2y$") {
+        return Ok("bcrypt".into());
+    }
+
+    Ok(PasswordHash::new(phc)?.algorithm.as_str().to_string())
+}
+
+/// Dispatches `verify`/`needs_rehash` to whichever registered [Hasher] matches a stored hash's
+/// algorithm id, and always `hash`es new passwords under the configured default backend.
+pub struct HasherRegistry {
+    default: &'static str,
+    backends: HashMap<&'static str, Box<dyn Hasher>>,
+}
+
+impl HasherRegistry {
+    /// Create a registry whose default (used for new hashes) is `default`.
+    pub fn new(default: Box<dyn Hasher>) -> Self {
+        let mut backends = HashMap::new();
+        backends.insert(default.algorithm_id(), default);
+
+        let default_id = backends.keys().next().copied().unwrap();
+        HasherRegistry {
+            default: default_id,
+            backends,
+        }
+    }
+
+    /// Register an additional backend this registry can verify against (but will not use for
+    /// new hashes unless it is also the default).
+    pub fn register(&mut self, backend: Box<dyn Hasher>) {
+        self.backends.insert(backend.algorithm_id(), backend);
+    }
+
+    /// Hash `raw` under the configured default backend.
+    pub fn hash(&self, raw: &str) -> ResultPwd<String> {
+        self.backends[self.default].hash(raw)
+    }
+
+    /// Verify `raw` against `phc`, dispatching to whichever backend produced it.
+    pub fn verify(&self, raw: &str, phc: &str) -> ResultPwd<bool> {
+        self.backend_for(phc)?.verify(raw, phc)
+    }
+
+    /// Does `phc` need rehashing, either because it was not produced by the default backend or
+    /// because that backend considers its cost too weak?
+    pub fn needs_rehash(&self, phc: &str) -> ResultPwd<bool> {
+        let algo = sniff_algorithm_id(phc)?;
+        if algo != self.default {
+            return Ok(true);
+        }
+
+        Ok(self.backends[self.default].needs_rehash(phc))
+    }
+
+    fn backend_for(&self, phc: &str) -> ResultPwd<&dyn Hasher> {
+        let algo = sniff_algorithm_id(phc)?;
+        self.backends
+            .get(algo.as_str())
+            .map(|b| b.as_ref())
+            .ok_or(PasswordError::InvalidPasswordAlgo { provided: algo })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lenient_argon2() -> Argon2Params {
+        Argon2Params {
+            m_cost: 4096,
+            t_cost: 3,
+            p_cost: 1,
+            output_len: None,
+        }
+    }
+
+    #[test]
+    fn hashes_and_verifies_under_default() {
+        let registry = HasherRegistry::new(Box::new(Argon2Hasher(lenient_argon2())));
+        let phc = registry.hash("hi").unwrap();
+
+        assert!(registry.verify("hi", &phc).unwrap());
+        assert!(!registry.verify("wrong", &phc).unwrap());
+    }
+
+    #[test]
+    fn dispatches_verification_by_stored_algorithm() {
+        let mut registry = HasherRegistry::new(Box::new(Argon2Hasher(lenient_argon2())));
+        registry.register(Box::new(ScryptHasher(ScryptParams {
+            log_n: 14,
+            r: 8,
+            p: 1,
+            len: 32,
+        })));
+
+        let scrypt_phc = ScryptHasher(ScryptParams {
+            log_n: 14,
+            r: 8,
+            p: 1,
+            len: 32,
+        })
+        .hash("hi")
+        .unwrap();
+
+        assert!(registry.verify("hi", &scrypt_phc).unwrap());
+    }
+
+    #[test]
+    fn flags_non_default_algorithm_for_rehash() {
+        let mut registry = HasherRegistry::new(Box::new(Argon2Hasher(lenient_argon2())));
+        registry.register(Box::new(ScryptHasher(ScryptParams {
+            log_n: 14,
+            r: 8,
+            p: 1,
+            len: 32,
+        })));
+
+        let scrypt_phc = ScryptHasher(ScryptParams {
+            log_n: 14,
+            r: 8,
+            p: 1,
+            len: 32,
+        })
+        .hash("hi")
+        .unwrap();
+
+        assert!(registry.needs_rehash(&scrypt_phc).unwrap());
+    }
+}
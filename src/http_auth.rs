@@ -0,0 +1,80 @@
+//! Parse HTTP `Authorization` headers into this crate's [forms](crate::forms) types, so a web
+//! server can bridge straight into [crate::identity::IdentityProviderUserPwd::login] without
+//! hand-rolling [RFC 7617](https://www.rfc-editor.org/rfc/rfc7617) itself.
+
+use base64::Engine;
+
+use crate::errors::{AuthError, ResultAuth};
+use crate::forms::UserPassForm;
+
+/// Reject headers longer than this before doing any decoding work, so a client cannot force a
+/// large allocation with an oversized `Authorization` header.
+pub const MAX_HEADER_LEN: usize = 8 * 1024;
+
+/// Parse an `Authorization: Basic <base64>` header into a [UserPassForm].
+pub fn parse_basic(header: &str) -> ResultAuth<UserPassForm> {
+    if header.len() > MAX_HEADER_LEN {
+        return Err(AuthError::Other(
+            "Authorization header exceeds MAX_HEADER_LEN".into(),
+        ));
+    }
+
+    let encoded = header
+        .strip_prefix("Basic ")
+        .ok_or_else(|| AuthError::Other("not a Basic Authorization header".into()))?;
+
+    let decoded = base64::engine::general_purpose::STANDARD
+        .decode(encoded.trim())
+        .map_err(|e| AuthError::Other(Box::new(e)))?;
+
+    let decoded = String::from_utf8(decoded).map_err(|e| AuthError::Other(Box::new(e)))?;
+
+    let (username, pwd) = decoded
+        .split_once(':')
+        .ok_or_else(|| AuthError::Other("missing ':' separator in Basic credentials".into()))?;
+
+    Ok(UserPassForm::new(username, pwd))
+}
+
+/// Build the `WWW-Authenticate` challenge header value for `realm`, as sent in a `401` response
+/// to prompt a client for Basic credentials.
+pub fn basic_challenge(realm: &str) -> String {
+    format!("Basic realm=\"{}\"", realm)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_basic_header() {
+        // "alice:s3cret" base64-encoded.
+        let header = "Basic YWxpY2U6czNjcmV0";
+        let form = parse_basic(header).unwrap();
+
+        assert_eq!(form.username, "alice");
+        assert_eq!(form.pwd, "s3cret");
+    }
+
+    #[test]
+    fn rejects_non_basic_scheme() {
+        assert!(parse_basic("Bearer abc123").is_err());
+    }
+
+    #[test]
+    fn rejects_missing_colon() {
+        // "aliceonly" base64-encoded, no ':'.
+        assert!(parse_basic("Basic YWxpY2Vvbmx5").is_err());
+    }
+
+    #[test]
+    fn rejects_oversized_header() {
+        let huge = format!("Basic {}", "A".repeat(MAX_HEADER_LEN));
+        assert!(parse_basic(&huge).is_err());
+    }
+
+    #[test]
+    fn builds_challenge() {
+        assert_eq!(basic_challenge("example"), "Basic realm=\"example\"");
+    }
+}
@@ -1,12 +1,16 @@
 //! The [Identity] trait represent the "who" of a software that needs authentication.
 use std::collections::HashMap;
+use std::time::Duration;
 
-use crate::credentials::Credential;
+use crate::auth_id::{AuthCId, AuthZId, AuthZResolver};
+use crate::credentials::{Credential, CredentialCertificate};
 use crate::errors::{ResultAuth, ResultPwd};
-use crate::forms::{EmailPassForm, UserPassForm};
+use crate::forms::{EmailPassForm, TokenForm, UserPassForm, UserPassTotpForm};
+use crate::invitation::Invitation;
 use crate::prelude::AuthError;
-use crate::properties::Properties;
+use crate::properties::{Properties, TimeStamp};
 use crate::token::Token;
+use crate::totp::Totp;
 
 /// A default realm name
 pub const REALM_DEFAULT: &str = "GLOBAL";
@@ -42,6 +46,24 @@ pub trait Identity {
     }
     /// Verify the security challenge (like a password) is valid for this identity
     fn verify_challenge(&self, against: &str) -> ResultPwd<()>;
+    /// The [Totp] second factor configured for this identity, if any
+    fn totp(&self) -> Option<Totp> {
+        None
+    }
+    /// How many consecutive password failures are on record for this identity, compared by
+    /// [IdentityProviderUserPwd::lockout_threshold] to decide whether to lock it out.
+    fn password_failure_count(&self) -> u32 {
+        0
+    }
+    /// Is this identity administratively disabled (e.g. [crate::users::UserFlag::DISABLED])?
+    fn is_disabled(&self) -> bool {
+        false
+    }
+    /// Does this identity hold a permission (directly, via [crate::rbac::grants] wildcard
+    /// matching) granting `perm`?
+    fn has_permission(&self, perm: &str) -> bool {
+        self.permissions().iter().any(|held| crate::rbac::grants(held, perm))
+    }
 }
 
 /// An identity provider (IDP) is a service that can authenticate a user with a [crate::credentials] and return an Token.
@@ -62,14 +84,179 @@ pub trait IdentityProviderUserPwd: IdentityProvider<UserPassForm> {
 
     fn verify_password(&self, credentials: &UserPassForm) -> ResultAuth<Token> {
         if let Some(user) = self.find(&credentials.username)? {
-            user.verify_challenge(&credentials.pwd)?;
-            Ok(credentials.into())
+            if user.is_disabled() {
+                return Err(AuthError::AccountDisabled {
+                    identity_id: credentials.username.clone(),
+                });
+            }
+
+            if let Some(threshold) = self.lockout_threshold() {
+                if user.password_failure_count() >= threshold {
+                    return Err(AuthError::AccountLocked {
+                        identity_id: credentials.username.clone(),
+                    });
+                }
+            }
+
+            match user.verify_challenge(&credentials.pwd) {
+                Ok(()) => {
+                    self.on_password_verified(&credentials.username, &credentials.pwd);
+                    self.on_successful_login(&credentials.username);
+                    Ok(credentials.into())
+                }
+                Err(e) => {
+                    self.on_failed_attempt(&credentials.username);
+                    Err(e.into())
+                }
+            }
         } else {
             Err(AuthError::UserNotFound {
                 named: credentials.username.clone(),
             })
         }
     }
+
+    /// The failure count at which [Self::verify_password] rejects further attempts with
+    /// [AuthError::AccountLocked], or `None` (the default) to disable lockout entirely.
+    fn lockout_threshold(&self) -> Option<u32> {
+        None
+    }
+
+    /// Called after a failed password check, so a backing store can persist an incremented
+    /// failure count (e.g. via [crate::lockout::FailureTracker]). Default is a no-op.
+    fn on_failed_attempt(&self, _identity_id: &str) {}
+
+    /// Called after a successful login, so a backing store can reset the failure count.
+    /// Default is a no-op.
+    fn on_successful_login(&self, _identity_id: &str) {}
+
+    /// Called once a password has just been proven correct (before [Self::on_successful_login]),
+    /// carrying the raw password so a backing store can check it against
+    /// [crate::password::Password::validate_and_upgrade] or [crate::hashers::HasherRegistry],
+    /// and persist a rehash if the stored hash fell short of the current policy. Transparent
+    /// hash upgrade-on-login is opt-in: nothing rehashes unless a provider implements this.
+    /// Default is a no-op.
+    fn on_password_verified(&self, _identity_id: &str, _raw_password: &str) {}
+
+    /// Like [Self::on_failed_attempt], but additionally carrying the originating `IpAddr` of
+    /// the attempt, for providers that want to rate-limit by IP rather than (or in addition to)
+    /// identity id. `UserPassForm` carries no network metadata, so `verify_password` cannot call
+    /// this itself; a caller fronting this provider with an HTTP layer should call it directly
+    /// with the request's peer address. Defaults to delegating to [Self::on_failed_attempt].
+    fn on_failed_attempt_from(&self, identity_id: &str, _from: Option<std::net::IpAddr>) {
+        self.on_failed_attempt(identity_id)
+    }
+}
+
+/// An identity provider (IDP) that additionally requires a [Totp] second factor on top of
+/// [UserPassForm] credentials.
+pub trait IdentityProviderUserPwdTotp: IdentityProviderUserPwd {
+    /// Verify both the password and the TOTP code, requiring both factors to succeed.
+    ///
+    /// `now` is threaded in explicitly (rather than read from the clock) so the check is
+    /// deterministic and testable. Returns the identity's [Totp] alongside the [Token], with its
+    /// `last_used_step` replay guard advanced to the step that was just consumed; callers MUST
+    /// persist this updated [Totp] back to storage, or the same code can be replayed on every
+    /// subsequent call.
+    ///
+    /// This does not delegate to [IdentityProviderUserPwd::verify_password]: for a 2FA-enabled
+    /// account, a correct password is only half the login, so it must not yet reset the lockout
+    /// failure count or trigger a rehash via [IdentityProviderUserPwd::on_successful_login] /
+    /// [IdentityProviderUserPwd::on_password_verified]. [IdentityProviderUserPwd::on_failed_attempt]
+    /// fires for a wrong password as usual, but also for a wrong TOTP code, so an attacker who
+    /// already has the password cannot guess codes without limit; the success hooks only fire
+    /// once the TOTP step itself succeeds.
+    fn login_with_totp(&self, identity: &UserPassTotpForm, now: TimeStamp) -> ResultAuth<(Token, Totp)> {
+        let user = self
+            .find(&identity.username)?
+            .ok_or_else(|| AuthError::UserNotFound {
+                named: identity.username.clone(),
+            })?;
+
+        if user.is_disabled() {
+            return Err(AuthError::AccountDisabled {
+                identity_id: identity.username.clone(),
+            });
+        }
+
+        if let Some(threshold) = self.lockout_threshold() {
+            if user.password_failure_count() >= threshold {
+                return Err(AuthError::AccountLocked {
+                    identity_id: identity.username.clone(),
+                });
+            }
+        }
+
+        if let Err(e) = user.verify_challenge(&identity.pwd) {
+            self.on_failed_attempt(&identity.username);
+            return Err(e.into());
+        }
+
+        let mut totp = user.totp().ok_or(AuthError::TotpNotConfigured)?;
+        match totp.verify(&identity.totp_code, now, 1) {
+            Ok(()) => {
+                self.on_password_verified(&identity.username, &identity.pwd);
+                self.on_successful_login(&identity.username);
+                let form = UserPassForm::new(&identity.username, &identity.pwd);
+                Ok(((&form).into(), totp))
+            }
+            Err(_) => {
+                self.on_failed_attempt(&identity.username);
+                Err(AuthError::InvalidTotpCode)
+            }
+        }
+    }
+}
+
+/// An identity provider (IDP) that resolves the [AuthCId] behind a successful login to one or
+/// more [AuthZId] scopes the caller may act as, letting a single login switch scopes (e.g. a
+/// `+admin` sub-account) without re-authenticating.
+pub trait IdentityProviderAuthZ: IdentityProviderUserPwd + AuthZResolver {
+    /// Verify the password as usual, then confirm `selected` is one of the authenticated
+    /// identity's available [AuthZId] scopes.
+    fn login_as(&self, identity: &UserPassForm, selected: &AuthZId) -> ResultAuth<AuthZId> {
+        let token = self.verify_password(identity)?;
+        let auth = AuthCId(token.identity_id().to_string());
+        self.select(&auth, selected)
+    }
+}
+
+/// An identity provider (IDP) that can resolve an opaque [TokenForm], issued by an external
+/// system, directly to an identity without a password check.
+pub trait IdentityProviderLoginToken: IdentityProvider<TokenForm> {
+    fn login_with_token(&self, form: &TokenForm) -> ResultAuth<Self::Identity> {
+        self.find(&form.data)?.ok_or_else(|| AuthError::TokenNotFound {
+            token: form.data.clone(),
+        })
+    }
+}
+
+/// An identity provider (IDP) that can onboard a brand new user by redeeming a one-time
+/// [Invitation], instead of requiring the identity to already exist.
+pub trait IdentityProviderInvite: IdentityProviderUserPwd {
+    /// Issue a new invitation, optionally pinned to `for_email`, expiring `expires_in` from now.
+    fn create_invitation(&self, for_email: Option<&str>, expires_in: Duration) -> ResultAuth<Invitation>;
+
+    /// Redeem `inv` for a brand new identity authenticated with `form`.
+    ///
+    /// Conforming implementations must: validate `inv` exists and is unexpired (returning
+    /// [AuthError::InvalidInvitation] otherwise), enforce a [crate::password::PasswordIsSafe]
+    /// check on `form.pwd`, create and persist the identity, and invalidate `inv` atomically so
+    /// it cannot be redeemed a second time.
+    fn redeem_invitation(&self, inv: &Invitation, form: &UserPassForm) -> ResultAuth<Self::Identity>;
+}
+
+/// An identity provider (IDP) that can resolve a client certificate (or other public-key
+/// credential), keyed by [CredentialCertificate] fingerprint, directly to its owning identity,
+/// for mTLS/token-free machine-to-machine authentication.
+pub trait IdentityProviderCertificate: IdentityProvider<CredentialCertificate> {
+    /// Resolve `fingerprint` (e.g. a hex-encoded SHA-256 of the certificate DER) to its owning
+    /// identity. Implementations backing `find` with an in-memory fingerprint list (rather than
+    /// a hashed lookup, which is already immune to this) should compare in constant time to
+    /// avoid leaking how many leading bytes of a probed fingerprint matched.
+    fn find_by_certificate(&self, fingerprint: &str) -> ResultAuth<Option<Self::Identity>> {
+        self.find(fingerprint)
+    }
 }
 
 /// An identity provider (IDP) that can authenticate a user with [EmailPassForm] credential.
@@ -92,10 +279,12 @@ pub trait IdentityProviderEmailPwd: IdentityProvider<EmailPassForm> {
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use super::*;
     use crate::errors::ResultPwd;
     use crate::password::{Password, PasswordIsSafe};
-    use crate::prelude::AuthError;
+    use crate::session::{MemTokenStore, TokenStore};
     use crate::users::UserPass;
 
     const TEST_PWD: &str = "1";
@@ -112,39 +301,53 @@ mod tests {
 
     struct TestProvider {
         users: [UserPass; 2],
+        tokens: MemTokenStore,
     }
 
     impl TestProvider {
         pub fn new() -> Self {
-            let p = Password::hash(TEST_PWD, ByPass {}).unwrap();
+            let p = Password::hash_check(TEST_PWD, ByPass {}).unwrap();
             let u1 = UserPass::new(USER_1, p.clone());
             let u2 = UserPass::new(USER_2, p);
 
-            TestProvider { users: [u1, u2] }
+            TestProvider {
+                users: [u1, u2],
+                tokens: MemTokenStore::new(),
+            }
         }
     }
 
-    impl IdentityProvider<UserPassForm, String> for TestProvider {
+    impl IdentityProvider<UserPassForm> for TestProvider {
         type Identity = UserPass;
 
         fn find(&self, id: &str) -> ResultAuth<Option<Self::Identity>> {
             Ok(self.users.iter().find(|x| x.identity_id() == id).cloned())
         }
 
-        fn find_by_token(&self, _token: &String) -> ResultAuth<Option<Self::Identity>> {
-            todo!()
+        fn find_by_token(&self, token: &Token) -> ResultAuth<Option<Self::Identity>> {
+            let now = chrono::Utc::now().fixed_offset();
+            match self.tokens.lookup(token, now) {
+                Some(id) => self.find(&id),
+                None => Ok(None),
+            }
         }
 
-        fn logout(&self, _token: &String) -> ResultAuth<bool> {
-            Ok(true)
+        fn logout(&self, token: &Token) -> ResultAuth<bool> {
+            Ok(self.tokens.revoke(token))
         }
     }
 
-    impl IdentityProviderUserPwd<String> for TestProvider {
-        fn verify_password(&self, credentials: &UserPassForm) -> ResultAuth<String> {
+    impl IdentityProviderUserPwd for TestProvider {
+        fn login(&self, identity: &UserPassForm) -> ResultAuth<Token> {
+            self.verify_password(identity)?;
+            let now = chrono::Utc::now().fixed_offset();
+            Ok(self.tokens.issue(&identity.username, now, Duration::from_secs(3600)))
+        }
+
+        fn verify_password(&self, credentials: &UserPassForm) -> ResultAuth<Token> {
             if let Some(user) = self.find(&credentials.username)? {
                 user.pwd.validate_password(&credentials.pwd)?;
-                Ok(credentials.username.clone())
+                Ok(credentials.into())
             } else {
                 Err(AuthError::UserNotFound {
                     named: credentials.username.clone(),
@@ -153,6 +356,141 @@ mod tests {
         }
     }
 
+    struct RehashingProvider {
+        users: [UserPass; 1],
+        verified: std::sync::Mutex<Vec<String>>,
+    }
+
+    impl IdentityProvider<UserPassForm> for RehashingProvider {
+        type Identity = UserPass;
+
+        fn find(&self, id: &str) -> ResultAuth<Option<Self::Identity>> {
+            Ok(self.users.iter().find(|x| x.identity_id() == id).cloned())
+        }
+
+        fn find_by_token(&self, _token: &Token) -> ResultAuth<Option<Self::Identity>> {
+            unimplemented!()
+        }
+
+        fn logout(&self, _token: &Token) -> ResultAuth<bool> {
+            unimplemented!()
+        }
+    }
+
+    impl IdentityProviderUserPwd for RehashingProvider {
+        fn on_password_verified(&self, identity_id: &str, raw_password: &str) {
+            self.verified
+                .lock()
+                .unwrap()
+                .push(format!("{identity_id}:{raw_password}"));
+        }
+    }
+
+    #[test]
+    fn on_password_verified_fires_on_successful_login_only() {
+        let p = Password::hash_check(TEST_PWD, ByPass {}).unwrap();
+        let idp = RehashingProvider {
+            users: [UserPass::new(USER_1, p)],
+            verified: std::sync::Mutex::new(vec![]),
+        };
+
+        assert!(idp
+            .verify_password(&UserPassForm::new(USER_1, "wrong"))
+            .is_err());
+        assert!(idp.verified.lock().unwrap().is_empty());
+
+        idp.verify_password(&UserPassForm::new(USER_1, TEST_PWD)).unwrap();
+        assert_eq!(
+            *idp.verified.lock().unwrap(),
+            vec![format!("{USER_1}:{TEST_PWD}")]
+        );
+    }
+
+    #[derive(Clone)]
+    struct TotpUser {
+        username: String,
+        pwd: Password,
+        totp: Totp,
+        failures: std::sync::Arc<std::sync::Mutex<u32>>,
+    }
+
+    impl Identity for TotpUser {
+        fn identity_id(&self) -> &str {
+            &self.username
+        }
+
+        fn verify_challenge(&self, against: &str) -> ResultPwd<()> {
+            self.pwd.validate_password(against)
+        }
+
+        fn totp(&self) -> Option<Totp> {
+            Some(self.totp.clone())
+        }
+
+        fn password_failure_count(&self) -> u32 {
+            *self.failures.lock().unwrap()
+        }
+    }
+
+    struct LockoutProvider {
+        user: TotpUser,
+    }
+
+    impl IdentityProvider<UserPassForm> for LockoutProvider {
+        type Identity = TotpUser;
+
+        fn find(&self, id: &str) -> ResultAuth<Option<Self::Identity>> {
+            Ok((id == self.user.identity_id()).then(|| self.user.clone()))
+        }
+
+        fn find_by_token(&self, _token: &Token) -> ResultAuth<Option<Self::Identity>> {
+            unimplemented!()
+        }
+
+        fn logout(&self, _token: &Token) -> ResultAuth<bool> {
+            unimplemented!()
+        }
+    }
+
+    impl IdentityProviderUserPwd for LockoutProvider {
+        fn lockout_threshold(&self) -> Option<u32> {
+            Some(3)
+        }
+
+        fn on_failed_attempt(&self, _identity_id: &str) {
+            *self.user.failures.lock().unwrap() += 1;
+        }
+
+        fn on_successful_login(&self, _identity_id: &str) {
+            *self.user.failures.lock().unwrap() = 0;
+        }
+    }
+
+    impl IdentityProviderUserPwdTotp for LockoutProvider {}
+
+    #[test]
+    fn totp_failure_does_not_reset_on_correct_password_alone() {
+        // A correct password followed by a wrong TOTP code must still count as a failed
+        // attempt, and must not have already reset the counter when the password was checked.
+        let user = TotpUser {
+            username: USER_1.into(),
+            pwd: Password::hash_check(TEST_PWD, ByPass {}).unwrap(),
+            totp: Totp::generate(),
+            failures: std::sync::Arc::new(std::sync::Mutex::new(0)),
+        };
+        let idp = LockoutProvider { user: user.clone() };
+        let now = chrono::Utc::now().fixed_offset();
+
+        let form = UserPassTotpForm::new(USER_1, TEST_PWD, "000000");
+        assert!(matches!(
+            idp.login_with_totp(&form, now),
+            Err(AuthError::InvalidTotpCode)
+        ));
+        // The password alone must not have reset the (still-zero) failure count to zero again
+        // via on_successful_login; it must instead be bumped by the failed TOTP step.
+        assert_eq!(user.password_failure_count(), 1);
+    }
+
     #[test]
     fn user_provider() {
         let idp = TestProvider::new();
@@ -162,6 +500,69 @@ mod tests {
         let mut form = UserPassForm::new(USER_1, "wrong");
         assert!(idp.login(&form).is_err());
         form.pwd = TEST_PWD.into();
-        assert!(idp.login(&form).is_ok());
+        let token = idp.login(&form).unwrap();
+
+        let found = idp.find_by_token(&token).unwrap();
+        assert_eq!(found.map(|u| u.username), Some(USER_1.to_string()));
+
+        assert!(idp.logout(&token).unwrap());
+        assert!(idp.find_by_token(&token).unwrap().is_none());
+    }
+
+    struct CertIdentity {
+        fingerprint: String,
+    }
+
+    impl Identity for CertIdentity {
+        fn identity_id(&self) -> &str {
+            &self.fingerprint
+        }
+
+        fn verify_challenge(&self, _against: &str) -> ResultPwd<()> {
+            Ok(())
+        }
+    }
+
+    struct CertProvider {
+        identities: [CertIdentity; 1],
+    }
+
+    impl IdentityProvider<CredentialCertificate> for CertProvider {
+        type Identity = CertIdentity;
+
+        fn find(&self, id: &str) -> ResultAuth<Option<Self::Identity>> {
+            Ok(self
+                .identities
+                .iter()
+                .find(|x| x.identity_id() == id)
+                .map(|x| CertIdentity {
+                    fingerprint: x.fingerprint.clone(),
+                }))
+        }
+
+        fn find_by_token(&self, _token: &Token) -> ResultAuth<Option<Self::Identity>> {
+            unimplemented!()
+        }
+
+        fn logout(&self, _token: &Token) -> ResultAuth<bool> {
+            unimplemented!()
+        }
+    }
+
+    impl IdentityProviderCertificate for CertProvider {}
+
+    #[test]
+    fn find_by_certificate_delegates_to_find() {
+        let idp = CertProvider {
+            identities: [CertIdentity {
+                fingerprint: "ab:cd:ef".into(),
+            }],
+        };
+
+        assert_eq!(
+            idp.find_by_certificate("ab:cd:ef").unwrap().map(|x| x.fingerprint),
+            Some("ab:cd:ef".to_string())
+        );
+        assert!(idp.find_by_certificate("unknown").unwrap().is_none());
     }
 }
@@ -0,0 +1,108 @@
+//! One-time invitation tokens for closed-signup registration, redeemed via
+//! [crate::identity::IdentityProviderInvite].
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::properties::TimeStamp;
+use crate::session::generate_opaque_token;
+
+/// An opaque, one-time invitation token issued by
+/// [crate::identity::IdentityProviderInvite::create_invitation].
+#[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct Invitation(pub String);
+
+struct Entry {
+    for_email: Option<String>,
+    expire: TimeStamp,
+}
+
+/// Issues and atomically redeems [Invitation] tokens.
+pub trait InvitationStore {
+    /// Issue a new invitation, optionally pinned to `for_email`, expiring `ttl` after `now`.
+    fn issue(&self, for_email: Option<&str>, now: TimeStamp, ttl: Duration) -> Invitation;
+    /// Check that `inv` exists and is unexpired, then invalidate it in the same step so it
+    /// cannot be redeemed again. Returns the `for_email` it was issued for, if any.
+    fn redeem(&self, inv: &Invitation, now: TimeStamp) -> Option<Option<String>>;
+}
+
+/// An in-memory [InvitationStore], suitable for a single-process deployment or tests.
+#[derive(Default)]
+pub struct MemInvitationStore {
+    invitations: Mutex<HashMap<String, Entry>>,
+}
+
+impl MemInvitationStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl InvitationStore for MemInvitationStore {
+    fn issue(&self, for_email: Option<&str>, now: TimeStamp, ttl: Duration) -> Invitation {
+        let code = generate_opaque_token();
+        let expire =
+            now + chrono::Duration::from_std(ttl).unwrap_or_else(|_| chrono::Duration::zero());
+
+        self.invitations.lock().unwrap().insert(
+            code.clone(),
+            Entry {
+                for_email: for_email.map(String::from),
+                expire,
+            },
+        );
+
+        Invitation(code)
+    }
+
+    fn redeem(&self, inv: &Invitation, now: TimeStamp) -> Option<Option<String>> {
+        // Removing unconditionally on first sight, win or lose, is what makes this atomic: a
+        // second concurrent redemption of the same code finds nothing, expired or not.
+        let entry = self.invitations.lock().unwrap().remove(&inv.0)?;
+
+        if entry.expire <= now {
+            return None;
+        }
+
+        Some(entry.for_email)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> TimeStamp {
+        chrono::DateTime::from_timestamp(0, 0).unwrap().fixed_offset()
+    }
+
+    #[test]
+    fn issues_and_redeems_invitation() {
+        let store = MemInvitationStore::new();
+        let inv = store.issue(Some("alice@example.com"), now(), Duration::from_secs(3600));
+
+        assert_eq!(
+            store.redeem(&inv, now()),
+            Some(Some("alice@example.com".to_string()))
+        );
+    }
+
+    #[test]
+    fn rejects_expired_invitation() {
+        let store = MemInvitationStore::new();
+        let inv = store.issue(None, now(), Duration::from_secs(60));
+
+        let later = now() + chrono::Duration::seconds(61);
+        assert_eq!(store.redeem(&inv, later), None);
+    }
+
+    #[test]
+    fn cannot_redeem_twice() {
+        let store = MemInvitationStore::new();
+        let inv = store.issue(None, now(), Duration::from_secs(60));
+
+        assert!(store.redeem(&inv, now()).is_some());
+        assert_eq!(store.redeem(&inv, now()), None);
+    }
+}
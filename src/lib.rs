@@ -1,18 +1,44 @@
+pub mod auth_id;
 pub mod credentials;
 mod errors;
 pub mod forms;
+pub mod hashers;
+pub mod http_auth;
 pub mod identity;
+pub mod invitation;
+pub mod lockout;
 pub mod password;
 mod properties;
+pub mod pwned;
+pub mod rbac;
+pub mod session;
+pub mod store;
 pub mod token;
+pub mod totp;
 pub mod users;
 
 pub mod prelude {
+    pub use crate::auth_id::{AuthCId, AuthZId, AuthZResolver};
     pub use crate::credentials::Credential;
-    pub use crate::errors::{AuthError, PasswordError, ResultAuth, ResultPwd};
-    pub use crate::identity::{Identity, IdentityProvider, IdentityProviderUserPwd, REALM_DEFAULT};
+    pub use crate::errors::{
+        AuthError, PasswordError, ResultAuth, ResultPwd, ResultStore, StoreError,
+    };
+    pub use crate::hashers::{Argon2Hasher, BcryptHasher, Hasher, HasherRegistry, ScryptHasher};
+    pub use crate::identity::{
+        Identity, IdentityProvider, IdentityProviderAuthZ, IdentityProviderCertificate,
+        IdentityProviderInvite, IdentityProviderLoginToken, IdentityProviderUserPwd,
+        IdentityProviderUserPwdTotp, REALM_DEFAULT,
+    };
+    pub use crate::invitation::{Invitation, InvitationStore, MemInvitationStore};
+    pub use crate::lockout::{FailureTracker, MemFailureTracker};
     pub use crate::password;
-    pub use crate::password::{Password, PasswordIsSafe};
+    pub use crate::password::{
+        Argon2Params, Password, PasswordIsSafe, PasswordPolicy, ScryptParams,
+    };
     pub use crate::properties::Properties;
+    pub use crate::rbac::{grants, Authorizer, Permission, RbacError, Role, RoleGraph};
+    pub use crate::session::{MemTokenStore, SessionStore, TokenStore};
+    pub use crate::store::{CredentialStore, JsonFileStore, StoredIdentity};
     pub use crate::token::Token;
+    pub use crate::totp::Totp;
 }
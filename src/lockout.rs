@@ -0,0 +1,76 @@
+//! Failure-count tracking for brute-force protection, used by
+//! [crate::identity::IdentityProviderUserPwd]'s `lockout_threshold`/`on_failed_attempt` hooks.
+//!
+//! Tracking is keyed by an arbitrary `&str` rather than tied to [crate::identity::Identity]
+//! directly, so a provider can track by identity id, by a `format!("ip:{addr}")` key for
+//! per-IP rate limiting, or both.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Counts failed attempts per key, for a provider to compare against its lockout threshold.
+pub trait FailureTracker {
+    /// Record a failed attempt for `key`, returning the new total failure count.
+    fn record_failure(&self, key: &str) -> u32;
+    /// Reset `key`'s failure count, e.g. after a successful login.
+    fn reset(&self, key: &str);
+    /// The current failure count for `key` (`0` if never recorded).
+    fn failure_count(&self, key: &str) -> u32;
+}
+
+/// An in-memory [FailureTracker], suitable for a single-process deployment or tests.
+#[derive(Default)]
+pub struct MemFailureTracker {
+    counts: Mutex<HashMap<String, u32>>,
+}
+
+impl MemFailureTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl FailureTracker for MemFailureTracker {
+    fn record_failure(&self, key: &str) -> u32 {
+        let mut counts = self.counts.lock().unwrap();
+        let count = counts.entry(key.into()).or_insert(0);
+        *count += 1;
+        *count
+    }
+
+    fn reset(&self, key: &str) {
+        self.counts.lock().unwrap().remove(key);
+    }
+
+    fn failure_count(&self, key: &str) -> u32 {
+        self.counts.lock().unwrap().get(key).copied().unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_and_resets_failures() {
+        let tracker = MemFailureTracker::new();
+        assert_eq!(tracker.failure_count("alice"), 0);
+
+        assert_eq!(tracker.record_failure("alice"), 1);
+        assert_eq!(tracker.record_failure("alice"), 2);
+        assert_eq!(tracker.failure_count("alice"), 2);
+
+        tracker.reset("alice");
+        assert_eq!(tracker.failure_count("alice"), 0);
+    }
+
+    #[test]
+    fn tracks_keys_independently() {
+        let tracker = MemFailureTracker::new();
+        tracker.record_failure("alice");
+        tracker.record_failure("ip:10.0.0.1");
+
+        assert_eq!(tracker.failure_count("alice"), 1);
+        assert_eq!(tracker.failure_count("ip:10.0.0.1"), 1);
+    }
+}
@@ -35,7 +35,7 @@ use rand_core::OsRng;
 
 pub mod hash_argon2 {
     use crate::password::Password;
-    use argon2::Argon2;
+    use argon2::{Algorithm, Argon2, Params, Version};
     use password_hash::{Ident, PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
 
     //List of the internal algos define for `argon2`
@@ -49,7 +49,16 @@ pub mod hash_argon2 {
         raw: &'a str,
         salt: &'a SaltString,
     ) -> password_hash::Result<PasswordHash<'a>> {
-        Argon2::default().hash_password(raw.as_ref(), salt.as_ref())
+        Argon2::default().hash_password(raw.as_ref(), salt)
+    }
+
+    pub(crate) fn hash_password_with_params<'a>(
+        raw: &'a str,
+        salt: &'a SaltString,
+        params: Params,
+    ) -> password_hash::Result<PasswordHash<'a>> {
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password(raw.as_ref(), salt)
     }
 
     pub(crate) fn validate_password(of: &Password, against: &str) -> password_hash::Result<()> {
@@ -62,7 +71,7 @@ pub mod hash_scrypt {
     use crate::password::Password;
     use password_hash::{Ident, PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
     use scrypt;
-    use scrypt::Scrypt;
+    use scrypt::{Params, Scrypt};
 
     //List of the internal algos define for `scrypt`
     pub(crate) const SCRYPT_IDENT: &[Ident<'_>] = &[scrypt::ALG_ID];
@@ -71,7 +80,15 @@ pub mod hash_scrypt {
         raw: &'a str,
         salt: &'a SaltString,
     ) -> password_hash::Result<PasswordHash<'a>> {
-        Scrypt.hash_password(raw.as_ref(), salt.as_ref())
+        Scrypt.hash_password(raw.as_ref(), salt)
+    }
+
+    pub(crate) fn hash_password_with_params<'a>(
+        raw: &'a str,
+        salt: &'a SaltString,
+        params: Params,
+    ) -> password_hash::Result<PasswordHash<'a>> {
+        Scrypt.hash_password_customized(raw.as_ref(), None, None, params, salt)
     }
 
     pub(crate) fn validate_password(of: &Password, against: &str) -> password_hash::Result<()> {
@@ -168,11 +185,13 @@ impl Password {
     }
 
     /// Load a password from a PCH formatted string. (Use this for load from a Storage)
+    ///
+    /// [PasswordAlgo::is_safe] only bounds the length of a *raw* password being hashed, so it is
+    /// not applied here: an already-encoded PHC string is routinely longer than that limit (a
+    /// real scrypt hash is well over 72 chars) and checking it would reject valid stored hashes.
     pub fn new(phc: &str) -> ResultPwd<Self> {
         let hash = PasswordHash::new(phc)?;
         let algo: PasswordAlgo = (&hash).try_into()?;
-        //Check the max size
-        algo.is_safe(phc)?;
         Ok(Self::_new(hash, algo))
     }
 
@@ -235,7 +254,7 @@ impl Password {
     /// # Safety
     ///
     /// At this point the internal string is always a correct PHC in the defined [PasswordAlgo]
-    pub fn get_hash(&self) -> PasswordHash {
+    pub fn get_hash(&self) -> PasswordHash<'_> {
         PasswordHash::new(&self.phc).unwrap()
     }
 
@@ -271,6 +290,138 @@ impl Password {
     pub fn salt() -> SaltString {
         SaltString::generate(&mut OsRng)
     }
+
+    /// Does this password's stored hash fall short of `policy` (wrong algorithm, or any cost
+    /// parameter weaker than required)?
+    pub fn needs_rehash(&self, policy: &PasswordPolicy) -> bool {
+        let hash = self.get_hash();
+
+        match policy {
+            PasswordPolicy::Argon2(p) => {
+                self.algo != PasswordAlgo::Argon2
+                    || param(&hash, "m") < Some(p.m_cost)
+                    || param(&hash, "t") < Some(p.t_cost)
+                    || param(&hash, "p") < Some(p.p_cost)
+            }
+            PasswordPolicy::Scrypt(p) => {
+                self.algo != PasswordAlgo::Scrypt
+                    || param(&hash, "ln") < Some(p.log_n as u32)
+                    || param(&hash, "r") < Some(p.r)
+                    || param(&hash, "p") < Some(p.p)
+            }
+        }
+    }
+
+    /// Verify `raw` as today, then rehash it under `policy` if [Self::needs_rehash] says the
+    /// stored hash is stale, returning the replacement for the caller to persist.
+    pub fn validate_and_upgrade(
+        &self,
+        raw: &str,
+        policy: &PasswordPolicy,
+    ) -> ResultPwd<Option<Password>> {
+        self.validate_password(raw)?;
+
+        if self.needs_rehash(policy) {
+            // `raw` already proved itself against the stored hash above, so the safety checker
+            // here is a deliberate no-op rather than re-running whatever policy produced it.
+            Ok(Some(match policy {
+                PasswordPolicy::Argon2(p) => Self::hash_argon_with(raw, *p, AlreadyValidated)?,
+                PasswordPolicy::Scrypt(p) => Self::hash_scrypt_with(raw, *p, AlreadyValidated)?,
+            }))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Hash a raw string into a PHC salted string using [argon2::Argon2] under explicit,
+    /// caller-chosen cost parameters, rather than the library defaults.
+    pub fn hash_argon_with(
+        raw: &str,
+        params: Argon2Params,
+        check: impl PasswordIsSafe,
+    ) -> ResultPwd<Self> {
+        check.is_safe(raw)?;
+
+        let params = argon2::Params::new(
+            params.m_cost,
+            params.t_cost,
+            params.p_cost,
+            params.output_len,
+        )
+        .map_err(|e| PasswordError::InvalidParams {
+            reason: e.to_string(),
+        })?;
+
+        let salt = Password::salt();
+        let hash = hash_argon2::hash_password_with_params(raw, &salt, params)?;
+        Ok(Self::_new(hash, PasswordAlgo::Argon2))
+    }
+
+    /// Hash a raw string into a PHC salted string using [scrypt::Scrypt] under explicit,
+    /// caller-chosen cost parameters, rather than the library's interactive default.
+    pub fn hash_scrypt_with(
+        raw: &str,
+        params: ScryptParams,
+        check: impl PasswordIsSafe,
+    ) -> ResultPwd<Self> {
+        check.is_safe(raw)?;
+
+        let params = scrypt::Params::new(params.log_n, params.r, params.p, params.len).map_err(
+            |e| PasswordError::InvalidParams {
+                reason: e.to_string(),
+            },
+        )?;
+
+        let salt = Password::salt();
+        let hash = hash_scrypt::hash_password_with_params(raw, &salt, params)?;
+        Ok(Self::_new(hash, PasswordAlgo::Scrypt))
+    }
+}
+
+/// A helper to pull a numeric PHC parameter (e.g. `m`, `t`, `p`, `ln`) out of a hash, used by
+/// [Password::needs_rehash] to compare stored cost parameters against a [PasswordPolicy].
+fn param(hash: &PasswordHash, key: &str) -> Option<u32> {
+    hash.params.get(key)?.as_str().parse().ok()
+}
+
+/// A [PasswordIsSafe] that always passes, used internally where the raw password was already
+/// proven safe by an earlier step (e.g. a successful login) so re-checking it would be wasted
+/// work.
+struct AlreadyValidated;
+
+impl PasswordIsSafe for AlreadyValidated {
+    fn is_safe(&self, _raw: &str) -> ResultPwd<()> {
+        Ok(())
+    }
+}
+
+/// Explicit Argon2 cost parameters, as in [OWASP's recommendations](https://cheatsheetseries.owasp.org/cheatsheets/Password_Storage_Cheat_Sheet.html#argon2id).
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Argon2Params {
+    pub m_cost: u32,
+    pub t_cost: u32,
+    pub p_cost: u32,
+    pub output_len: Option<usize>,
+}
+
+/// Explicit Scrypt cost parameters.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct ScryptParams {
+    pub log_n: u8,
+    pub r: u32,
+    pub p: u32,
+    pub len: usize,
+}
+
+/// The target algorithm and cost parameters a [Password] is expected to be hashed with.
+///
+/// OWASP recommends periodically raising these as hardware gets faster; comparing a stored
+/// hash against a [PasswordPolicy] is how [Password::needs_rehash] decides whether a login
+/// should trigger a silent rehash.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum PasswordPolicy {
+    Argon2(Argon2Params),
+    Scrypt(ScryptParams),
 }
 
 impl FromStr for Password {
@@ -307,4 +458,76 @@ mod tests {
 
         assert_eq!(p, "hi")
     }
+
+    #[test]
+    fn rehash_policy() {
+        let p = Password::new("$argon2id$v=19$m=4096,t=3,p=1$B+wShXe3YjVd5C8oh4x3pw$XxZJ3BnZMGnBNwPnXrvVM4MMAeFzxf9yxkbXAPcvBzQ").unwrap();
+
+        let lenient = PasswordPolicy::Argon2(Argon2Params {
+            m_cost: 4096,
+            t_cost: 3,
+            p_cost: 1,
+            output_len: None,
+        });
+        assert!(!p.needs_rehash(&lenient));
+        assert!(p.validate_and_upgrade("hi", &lenient).unwrap().is_none());
+
+        let stricter = PasswordPolicy::Argon2(Argon2Params {
+            m_cost: 19456,
+            t_cost: 3,
+            p_cost: 1,
+            output_len: None,
+        });
+        assert!(p.needs_rehash(&stricter));
+
+        let upgraded = p.validate_and_upgrade("hi", &stricter).unwrap().unwrap();
+        assert!(!upgraded.needs_rehash(&stricter));
+        assert_eq!(upgraded, "hi");
+    }
+
+    #[test]
+    fn hash_with_explicit_params() {
+        let p = Password::hash_argon_with(
+            "12345678",
+            Argon2Params {
+                m_cost: 8192,
+                t_cost: 2,
+                p_cost: 1,
+                output_len: None,
+            },
+            CHECKER_MIN_SIZE,
+        )
+        .unwrap();
+        assert_eq!(p, "12345678");
+
+        let s = Password::hash_scrypt_with(
+            "12345678",
+            ScryptParams {
+                log_n: 14,
+                r: 8,
+                p: 1,
+                len: 32,
+            },
+            CHECKER_MIN_SIZE,
+        )
+        .unwrap();
+        assert_eq!(s, "12345678");
+    }
+
+    #[test]
+    fn rejects_invalid_params() {
+        let err = Password::hash_argon_with(
+            "12345678",
+            Argon2Params {
+                m_cost: 0,
+                t_cost: 0,
+                p_cost: 0,
+                output_len: None,
+            },
+            CHECKER_MIN_SIZE,
+        )
+        .unwrap_err();
+
+        assert!(matches!(err, PasswordError::InvalidParams { .. }));
+    }
 }
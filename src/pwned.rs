@@ -0,0 +1,125 @@
+//! An offline "have you been pwned"-style checker for [crate::password::PasswordIsSafe].
+//!
+//! Uses the [k-anonymity range query](https://www.troyhunt.com/ive-just-launched-pwned-passwords-v2/)
+//! scheme: the raw password is SHA-1 hashed and only the first 5 hex chars (the "prefix") are
+//! ever used to look anything up, so neither the password nor its full hash has to leave the
+//! caller's machine. The remaining 35 chars (the "suffix") are matched locally against whatever
+//! [PwnedRange] returns for that prefix.
+
+use sha1::{Digest, Sha1};
+
+use crate::errors::{PasswordError, ResultPwd};
+use crate::password::PasswordIsSafe;
+
+/// A source of breached-password suffixes for a given SHA-1 prefix.
+///
+/// Implement this against a bundled file, an in-memory set, or a network fetcher; this crate
+/// stays free of any network dependency by only depending on the trait.
+pub trait PwnedRange {
+    /// Return the `SUFFIX:COUNT` lines (count being how many times that password was seen in
+    /// a breach) known for the given 5-char uppercase-hex `prefix`.
+    fn suffixes(&self, prefix: &str) -> ResultPwd<Vec<String>>;
+}
+
+/// A [PwnedRange] backed by an in-memory set of full `SUFFIX:COUNT` lines, keyed by prefix.
+///
+/// Useful for tests, or for small deployments that load a bundled breach-list file up front.
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryPwnedRange {
+    entries: std::collections::HashMap<String, Vec<String>>,
+}
+
+impl InMemoryPwnedRange {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Insert a `SUFFIX:COUNT` line under its 5-char prefix.
+    pub fn insert(&mut self, prefix: &str, suffix_and_count: &str) {
+        self.entries
+            .entry(prefix.to_ascii_uppercase())
+            .or_default()
+            .push(suffix_and_count.into());
+    }
+}
+
+impl PwnedRange for InMemoryPwnedRange {
+    fn suffixes(&self, prefix: &str) -> ResultPwd<Vec<String>> {
+        Ok(self
+            .entries
+            .get(&prefix.to_ascii_uppercase())
+            .cloned()
+            .unwrap_or_default())
+    }
+}
+
+/// A [PasswordIsSafe] checker that rejects passwords found in a breach corpus, via `range`.
+pub struct CheckPwned<R: PwnedRange> {
+    range: R,
+}
+
+impl<R: PwnedRange> CheckPwned<R> {
+    pub fn new(range: R) -> Self {
+        CheckPwned { range }
+    }
+}
+
+impl<R: PwnedRange> PasswordIsSafe for CheckPwned<R> {
+    fn is_safe(&self, raw: &str) -> ResultPwd<()> {
+        let digest = hex_upper(&Sha1::digest(raw.as_bytes()));
+        let (prefix, suffix) = digest.split_at(5);
+
+        for line in self.range.suffixes(prefix)? {
+            let Some((candidate, count)) = line.split_once(':') else {
+                continue;
+            };
+
+            if candidate.eq_ignore_ascii_case(suffix) {
+                let occurrences: u64 = count.trim().parse().unwrap_or(0);
+                if occurrences > 0 {
+                    return Err(PasswordError::CompromisedPassword { occurrences });
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn hex_upper(bytes: &[u8]) -> String {
+    use std::fmt::Write;
+
+    let mut out = String::with_capacity(bytes.len() * 2);
+    for b in bytes {
+        write!(out, "{:02X}", b).unwrap();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_known_breached_password() {
+        // SHA-1("password") = 5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8
+        let mut range = InMemoryPwnedRange::new();
+        range.insert("5BAA6", "1E4C9B93F3F0682250B6CF8331B7EE68FD8:3730471");
+
+        let checker = CheckPwned::new(range);
+        match checker.is_safe("password") {
+            Err(PasswordError::CompromisedPassword { occurrences }) => {
+                assert_eq!(occurrences, 3730471)
+            }
+            other => panic!("expected CompromisedPassword, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn allows_unknown_password() {
+        let range = InMemoryPwnedRange::new();
+        let checker = CheckPwned::new(range);
+
+        assert!(checker.is_safe("a sufficiently unusual passphrase").is_ok());
+    }
+}
@@ -0,0 +1,257 @@
+//! Role-based access control on top of [crate::identity::Identity]'s flat `permissions()`/`roles()`.
+//!
+//! A [RoleGraph] holds named [Role]s, each with its own permissions and optional parent roles,
+//! and resolves a role's *effective* permissions by transitively unioning its parents. Both
+//! stored and requested permissions are dotted hierarchical strings (e.g. `lab.test.write`),
+//! matched with [grants] so a role holding `lab.test.*` or `lab.*` authorizes the narrower one.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::identity::Identity;
+
+/// A dotted hierarchical permission string (e.g. `lab.test.write`), with wildcard-aware
+/// matching against another [Permission] via [Self::grants].
+#[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct Permission(pub String);
+
+impl Permission {
+    pub fn new(value: &str) -> Self {
+        Permission(value.into())
+    }
+
+    /// Does this permission (as held) grant `requested`? See [grants] for the matching rules.
+    pub fn grants(&self, requested: &Permission) -> bool {
+        grants(&self.0, &requested.0)
+    }
+}
+
+impl From<&str> for Permission {
+    fn from(value: &str) -> Self {
+        Permission::new(value)
+    }
+}
+
+/// Answers "may this identity do X", given its held roles/permissions and a requested
+/// [Permission].
+pub trait Authorizer {
+    fn can(&self, id: &impl Identity, perm: &Permission) -> bool;
+}
+
+impl Authorizer for RoleGraph {
+    fn can(&self, id: &impl Identity, perm: &Permission) -> bool {
+        self.can(id.roles(), &perm.0)
+    }
+}
+
+/// A named role: its own permissions, plus the names of roles it inherits from.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Role {
+    pub name: String,
+    pub permissions: Vec<String>,
+    pub parents: Vec<String>,
+}
+
+impl Role {
+    pub fn new(name: &str, permissions: Vec<String>, parents: Vec<String>) -> Self {
+        Role {
+            name: name.into(),
+            permissions,
+            parents,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum RbacError {
+    /// The role graph contains a cycle reachable from this role.
+    CyclicInheritance { role: String },
+    /// A parent role name does not exist in the graph.
+    UnknownRole { role: String },
+}
+
+/// A graph of [Role]s that can resolve each role's effective (transitively inherited)
+/// permission set.
+#[derive(Debug, Clone, Default)]
+pub struct RoleGraph {
+    roles: HashMap<String, Role>,
+}
+
+impl RoleGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_role(&mut self, role: Role) {
+        self.roles.insert(role.name.clone(), role);
+    }
+
+    /// The permissions granted by `role_name`, including every permission inherited from its
+    /// parents (and their parents, ...). Errors if the role is unknown or its parent chain
+    /// cycles back to itself.
+    pub fn effective_permissions(&self, role_name: &str) -> Result<HashSet<String>, RbacError> {
+        let mut out = HashSet::new();
+        let mut visited = HashSet::new();
+        self.collect(role_name, &mut visited, &mut out)?;
+        Ok(out)
+    }
+
+    fn collect(
+        &self,
+        role_name: &str,
+        visited: &mut HashSet<String>,
+        out: &mut HashSet<String>,
+    ) -> Result<(), RbacError> {
+        if !visited.insert(role_name.into()) {
+            return Err(RbacError::CyclicInheritance {
+                role: role_name.into(),
+            });
+        }
+
+        let role = self.roles.get(role_name).ok_or_else(|| RbacError::UnknownRole {
+            role: role_name.into(),
+        })?;
+
+        out.extend(role.permissions.iter().cloned());
+
+        for parent in &role.parents {
+            self.collect(parent, visited, out)?;
+        }
+
+        // `visited` holds only the current inheritance path, so a role shared by two parents
+        // (a diamond) is not mistaken for a cycle.
+        visited.remove(role_name);
+        Ok(())
+    }
+
+    /// Does any of `held`'s effective permissions grant `requested`?
+    pub fn can(&self, held: &[String], requested: &str) -> bool {
+        held.iter().any(|role_name| {
+            self.effective_permissions(role_name)
+                .map(|perms| perms.iter().any(|held| grants(held, requested)))
+                .unwrap_or(false)
+        })
+    }
+}
+
+/// Does the dotted permission pattern `held` grant the dotted permission `requested`?
+///
+/// Both are split on `.` and matched segment-by-segment; a `*` segment in `held` matches the
+/// requested segment at that position *and* every segment after it (so `lab.*` grants
+/// `lab.test.write`, and `lab.test.*` grants `lab.test.write` but not `lab.other.write`).
+pub fn grants(held: &str, requested: &str) -> bool {
+    let held_segments: Vec<&str> = held.split('.').collect();
+    let requested_segments: Vec<&str> = requested.split('.').collect();
+
+    for (i, held_seg) in held_segments.iter().enumerate() {
+        if *held_seg == "*" {
+            return true;
+        }
+
+        match requested_segments.get(i) {
+            Some(req_seg) if req_seg == held_seg => continue,
+            _ => return false,
+        }
+    }
+
+    held_segments.len() == requested_segments.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_graph() -> RoleGraph {
+        let mut graph = RoleGraph::new();
+        graph.add_role(Role::new("base", vec!["lab.read".into()], vec![]));
+        graph.add_role(Role::new(
+            "tester",
+            vec!["lab.test.*".into()],
+            vec!["base".into()],
+        ));
+        graph
+    }
+
+    #[test]
+    fn wildcard_matching() {
+        assert!(grants("lab.test.*", "lab.test.write"));
+        assert!(grants("lab.*", "lab.test.write"));
+        assert!(!grants("lab.test.*", "lab.other.write"));
+        assert!(grants("lab.test.write", "lab.test.write"));
+        assert!(!grants("lab.test.write", "lab.test"));
+    }
+
+    #[test]
+    fn inherits_parent_permissions() {
+        let graph = sample_graph();
+        let effective = graph.effective_permissions("tester").unwrap();
+
+        assert!(effective.contains("lab.read"));
+        assert!(effective.contains("lab.test.*"));
+        assert!(graph.can(&["tester".into()], "lab.test.write"));
+        assert!(graph.can(&["tester".into()], "lab.read"));
+        assert!(!graph.can(&["tester".into()], "lab.other.write"));
+    }
+
+    #[test]
+    fn permission_wildcard_matching() {
+        let held = Permission::new("lab.test.*");
+        assert!(held.grants(&Permission::new("lab.test.write")));
+        assert!(!held.grants(&Permission::new("lab.other.write")));
+    }
+
+    #[test]
+    fn authorizer_checks_identity_roles() {
+        use crate::credentials::Credential;
+        use crate::errors::ResultPwd;
+
+        struct Operator {
+            roles: Vec<String>,
+        }
+
+        impl Identity for Operator {
+            fn identity_id(&self) -> &str {
+                "operator"
+            }
+
+            fn roles(&self) -> &[String] {
+                &self.roles
+            }
+
+            fn credentials(&self) -> Vec<Credential> {
+                vec![]
+            }
+
+            fn verify_challenge(&self, _against: &str) -> ResultPwd<()> {
+                Ok(())
+            }
+        }
+
+        let graph = sample_graph();
+        let operator = Operator {
+            roles: vec!["tester".into()],
+        };
+
+        assert!(Authorizer::can(
+            &graph,
+            &operator,
+            &Permission::new("lab.test.write")
+        ));
+        assert!(!Authorizer::can(
+            &graph,
+            &operator,
+            &Permission::new("lab.other.write")
+        ));
+    }
+
+    #[test]
+    fn detects_cycle() {
+        let mut graph = RoleGraph::new();
+        graph.add_role(Role::new("a", vec![], vec!["b".into()]));
+        graph.add_role(Role::new("b", vec![], vec!["a".into()]));
+
+        assert!(matches!(
+            graph.effective_permissions("a"),
+            Err(RbacError::CyclicInheritance { .. })
+        ));
+    }
+}
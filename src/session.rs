@@ -0,0 +1,173 @@
+//! A stateful session layer for opaque bearer tokens, filling in the `find_by_token`/`logout`
+//! holes left by [crate::identity::IdentityProvider].
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use base64::Engine;
+use rand_core::{OsRng, RngCore};
+
+use crate::credentials::CredentialToken;
+use crate::properties::TimeStamp;
+use crate::token::Token;
+
+/// Issues, resolves and revokes the opaque [Token]s handed out after a successful login.
+pub trait TokenStore {
+    /// Issue a new token for `identity_id`, expiring `ttl` after `now`.
+    fn issue(&self, identity_id: &str, now: TimeStamp, ttl: Duration) -> Token;
+    /// Resolve a token back to the identity id it was issued for, pruning it if expired.
+    fn lookup(&self, token: &Token, now: TimeStamp) -> Option<String>;
+    /// Revoke a token, returning whether one was found.
+    fn revoke(&self, token: &Token) -> bool;
+}
+
+struct Entry {
+    identity_id: String,
+    created_at: TimeStamp,
+    expire: TimeStamp,
+}
+
+/// A [TokenStore] that can additionally sweep every expired token at once, for a periodic
+/// background cleanup task rather than relying solely on pruning at lookup time.
+pub trait SessionStore: TokenStore {
+    /// Remove every token that has expired as of `now`, returning how many were swept.
+    fn sweep(&self, now: TimeStamp) -> usize;
+}
+
+/// An in-memory [TokenStore]/[SessionStore], suitable for a single-process deployment or tests.
+#[derive(Default)]
+pub struct MemTokenStore {
+    tokens: Mutex<HashMap<String, Entry>>,
+}
+
+impl MemTokenStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// When `token` was issued, or `None` if it's unknown, revoked, or already pruned as expired
+    /// (by [TokenStore::lookup] or [SessionStore::sweep]). Expiry is not checked here: an
+    /// expired token that has not been pruned yet still reports its issue time.
+    pub fn issued_at(&self, token: &Token) -> Option<TimeStamp> {
+        let data = token.identity_id();
+        self.tokens.lock().unwrap().get(data).map(|entry| entry.created_at)
+    }
+}
+
+/// A cryptographically random, URL-safe opaque token string (32 bytes of [OsRng] output).
+pub(crate) fn generate_opaque_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+}
+
+impl TokenStore for MemTokenStore {
+    fn issue(&self, identity_id: &str, now: TimeStamp, ttl: Duration) -> Token {
+        let data = generate_opaque_token();
+        let expire = now
+            + chrono::Duration::from_std(ttl).unwrap_or_else(|_| chrono::Duration::zero());
+
+        self.tokens.lock().unwrap().insert(
+            data.clone(),
+            Entry {
+                identity_id: identity_id.into(),
+                created_at: now,
+                expire,
+            },
+        );
+
+        Token::new(CredentialToken { data }.into(), Some(expire), None)
+    }
+
+    fn lookup(&self, token: &Token, now: TimeStamp) -> Option<String> {
+        let data = token.identity_id();
+        let mut tokens = self.tokens.lock().unwrap();
+
+        // A HashMap lookup is already immune to timing probes here: the bucket a key hashes to
+        // (and thus how long the lookup takes) is gated by a random per-process SipHash seed,
+        // not by how many leading bytes of `data` match a stored key.
+        let entry = tokens.get(data)?;
+
+        if entry.expire <= now {
+            tokens.remove(data);
+            return None;
+        }
+
+        Some(entry.identity_id.clone())
+    }
+
+    fn revoke(&self, token: &Token) -> bool {
+        let data = token.identity_id();
+        self.tokens.lock().unwrap().remove(data).is_some()
+    }
+}
+
+impl SessionStore for MemTokenStore {
+    fn sweep(&self, now: TimeStamp) -> usize {
+        let mut tokens = self.tokens.lock().unwrap();
+        let before = tokens.len();
+        tokens.retain(|_, entry| entry.expire > now);
+        before - tokens.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> TimeStamp {
+        chrono::DateTime::from_timestamp(0, 0).unwrap().fixed_offset()
+    }
+
+    #[test]
+    fn issues_and_resolves_token() {
+        let store = MemTokenStore::new();
+        let token = store.issue("user1", now(), Duration::from_secs(60));
+
+        assert_eq!(store.lookup(&token, now()), Some("user1".into()));
+    }
+
+    #[test]
+    fn issued_at_tracks_issue_time_and_clears_on_revoke() {
+        let store = MemTokenStore::new();
+        let token = store.issue("user1", now(), Duration::from_secs(60));
+
+        assert_eq!(store.issued_at(&token), Some(now()));
+
+        store.revoke(&token);
+        assert_eq!(store.issued_at(&token), None);
+    }
+
+    #[test]
+    fn rejects_and_prunes_expired_token() {
+        let store = MemTokenStore::new();
+        let token = store.issue("user1", now(), Duration::from_secs(60));
+
+        let later = now() + chrono::Duration::seconds(61);
+        assert_eq!(store.lookup(&token, later), None);
+        // Pruned: even going back to a valid time won't resurrect it.
+        assert_eq!(store.lookup(&token, now()), None);
+    }
+
+    #[test]
+    fn revoke_removes_token() {
+        let store = MemTokenStore::new();
+        let token = store.issue("user1", now(), Duration::from_secs(60));
+
+        assert!(store.revoke(&token));
+        assert!(!store.revoke(&token));
+        assert_eq!(store.lookup(&token, now()), None);
+    }
+
+    #[test]
+    fn sweep_removes_only_expired() {
+        let store = MemTokenStore::new();
+        let fresh = store.issue("user1", now(), Duration::from_secs(60));
+        let _stale = store.issue("user2", now(), Duration::from_secs(1));
+
+        let later = now() + chrono::Duration::seconds(2);
+        assert_eq!(store.sweep(later), 1);
+        assert_eq!(store.lookup(&fresh, later), Some("user1".into()));
+    }
+}
@@ -0,0 +1,262 @@
+//! A [CredentialStore] abstraction for the CRUD identity providers need, plus [JsonFileStore], a
+//! batteries-included file-backed implementation for small deployments that don't want to pull
+//! in a full database.
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{ResultStore, StoreError};
+
+/// The format version written into a [JsonFileStore]'s file, bumped whenever [FileFormat]'s
+/// shape changes in a way older readers can't handle.
+const FORMAT_VERSION: u32 = 1;
+
+/// A serializable identity record: just enough to reconstruct a username/password identity,
+/// independent of any single concrete [crate::identity::Identity] implementation.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct StoredIdentity {
+    pub identity_id: String,
+    /// The [crate::password::Password] PHC string.
+    pub phc: String,
+    pub realm: String,
+}
+
+/// The common CRUD an identity provider needs against a backing identity store.
+pub trait CredentialStore {
+    fn add(&self, record: StoredIdentity) -> ResultStore<()>;
+    fn get(&self, id: &str) -> ResultStore<StoredIdentity>;
+    fn update(&self, record: StoredIdentity) -> ResultStore<()>;
+    fn remove(&self, id: &str) -> ResultStore<()>;
+    fn list(&self) -> ResultStore<Vec<StoredIdentity>>;
+}
+
+#[derive(Serialize, Deserialize)]
+struct FileFormat {
+    version: u32,
+    identities: Vec<StoredIdentity>,
+}
+
+/// A [CredentialStore] backed by a single JSON file, loaded lazily on first access and written
+/// atomically (temp file + rename) on every mutation so a crash mid-write cannot corrupt it.
+pub struct JsonFileStore {
+    path: PathBuf,
+    cache: Mutex<Option<Vec<StoredIdentity>>>,
+}
+
+impl JsonFileStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        JsonFileStore {
+            path: path.into(),
+            cache: Mutex::new(None),
+        }
+    }
+
+    fn load(&self) -> ResultStore<Vec<StoredIdentity>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let data = fs::read_to_string(&self.path)?;
+        let format: FileFormat = serde_json::from_str(&data)
+            .map_err(|e| StoreError::StoreCorrupt { reason: e.to_string() })?;
+
+        if format.version != FORMAT_VERSION {
+            return Err(StoreError::StoreCorrupt {
+                reason: format!(
+                    "unsupported format version {} (expected {})",
+                    format.version, FORMAT_VERSION
+                ),
+            });
+        }
+
+        Ok(format.identities)
+    }
+
+    fn persist(&self, identities: &[StoredIdentity]) -> ResultStore<()> {
+        let format = FileFormat {
+            version: FORMAT_VERSION,
+            identities: identities.to_vec(),
+        };
+        let serialized = serde_json::to_string_pretty(&format)
+            .map_err(|e| StoreError::StoreCorrupt { reason: e.to_string() })?;
+
+        let tmp_path = self.path.with_extension("tmp");
+        fs::write(&tmp_path, serialized)?;
+        fs::rename(&tmp_path, &self.path)?;
+        Ok(())
+    }
+
+    /// Run `f` against the lazily-loaded in-memory identities, without persisting.
+    fn with_loaded<R>(
+        &self,
+        f: impl FnOnce(&[StoredIdentity]) -> ResultStore<R>,
+    ) -> ResultStore<R> {
+        let mut guard = self.cache.lock().unwrap();
+        if guard.is_none() {
+            *guard = Some(self.load()?);
+        }
+
+        f(guard.as_ref().unwrap())
+    }
+
+    /// Run `f` against the lazily-loaded in-memory identities, persisting the result afterward.
+    fn mutate<R>(
+        &self,
+        f: impl FnOnce(&mut Vec<StoredIdentity>) -> ResultStore<R>,
+    ) -> ResultStore<R> {
+        let mut guard = self.cache.lock().unwrap();
+        if guard.is_none() {
+            *guard = Some(self.load()?);
+        }
+
+        let identities = guard.as_mut().unwrap();
+        let result = f(identities)?;
+        self.persist(identities)?;
+        Ok(result)
+    }
+}
+
+impl CredentialStore for JsonFileStore {
+    fn add(&self, record: StoredIdentity) -> ResultStore<()> {
+        self.mutate(|identities| {
+            if identities.iter().any(|r| r.identity_id == record.identity_id) {
+                return Err(StoreError::StoreCorrupt {
+                    reason: format!("duplicate identity id {}", record.identity_id),
+                });
+            }
+
+            identities.push(record);
+            Ok(())
+        })
+    }
+
+    fn get(&self, id: &str) -> ResultStore<StoredIdentity> {
+        self.with_loaded(|identities| {
+            identities
+                .iter()
+                .find(|r| r.identity_id == id)
+                .cloned()
+                .ok_or_else(|| StoreError::CredentialNotFound { id: id.into() })
+        })
+    }
+
+    fn update(&self, record: StoredIdentity) -> ResultStore<()> {
+        self.mutate(|identities| {
+            let slot = identities
+                .iter_mut()
+                .find(|r| r.identity_id == record.identity_id)
+                .ok_or_else(|| StoreError::CredentialNotFound {
+                    id: record.identity_id.clone(),
+                })?;
+
+            *slot = record;
+            Ok(())
+        })
+    }
+
+    fn remove(&self, id: &str) -> ResultStore<()> {
+        self.mutate(|identities| {
+            let before = identities.len();
+            identities.retain(|r| r.identity_id != id);
+
+            if identities.len() == before {
+                return Err(StoreError::CredentialNotFound { id: id.into() });
+            }
+
+            Ok(())
+        })
+    }
+
+    fn list(&self) -> ResultStore<Vec<StoredIdentity>> {
+        self.with_loaded(|identities| Ok(identities.to_vec()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("forbidden-store-test-{name}-{}.json", std::process::id()))
+    }
+
+    fn record(id: &str) -> StoredIdentity {
+        StoredIdentity {
+            identity_id: id.into(),
+            phc: "$argon2id$v=19$m=4096,t=3,p=1$B+wShXe3YjVd5C8oh4x3pw$XxZJ3BnZMGnBNwPnXrvVM4MMAeFzxf9yxkbXAPcvBzQ".into(),
+            realm: "GLOBAL".into(),
+        }
+    }
+
+    #[test]
+    fn round_trips_through_the_file() {
+        let path = temp_path("round-trip");
+        let _ = fs::remove_file(&path);
+
+        let store = JsonFileStore::new(path.clone());
+        store.add(record("alice")).unwrap();
+
+        // A fresh store re-reads from disk rather than sharing the in-memory cache.
+        let reopened = JsonFileStore::new(path.clone());
+        assert_eq!(reopened.get("alice").unwrap(), record("alice"));
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn rejects_duplicate_add_and_missing_update_remove() {
+        let path = temp_path("dup-missing");
+        let _ = fs::remove_file(&path);
+
+        let store = JsonFileStore::new(path.clone());
+        store.add(record("alice")).unwrap();
+
+        assert!(matches!(
+            store.add(record("alice")).unwrap_err(),
+            StoreError::StoreCorrupt { .. }
+        ));
+        assert!(matches!(
+            store.update(record("bob")).unwrap_err(),
+            StoreError::CredentialNotFound { .. }
+        ));
+        assert!(matches!(
+            store.remove("bob").unwrap_err(),
+            StoreError::CredentialNotFound { .. }
+        ));
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn lists_and_removes() {
+        let path = temp_path("list-remove");
+        let _ = fs::remove_file(&path);
+
+        let store = JsonFileStore::new(path.clone());
+        store.add(record("alice")).unwrap();
+        store.add(record("bob")).unwrap();
+        assert_eq!(store.list().unwrap().len(), 2);
+
+        store.remove("alice").unwrap();
+        assert_eq!(store.list().unwrap(), vec![record("bob")]);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn rejects_mismatched_format_version() {
+        let path = temp_path("bad-version");
+        fs::write(&path, r#"{"version":99,"identities":[]}"#).unwrap();
+
+        let store = JsonFileStore::new(path.clone());
+        assert!(matches!(
+            store.list().unwrap_err(),
+            StoreError::StoreCorrupt { .. }
+        ));
+
+        fs::remove_file(&path).unwrap();
+    }
+}
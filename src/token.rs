@@ -40,8 +40,15 @@ impl Token {
             Credential::User(x) => &x.username,
             Credential::UserEmail(x) => &x.email,
             Credential::Token(x) => &x.data,
+            Credential::Totp(x) => &x.code,
+            Credential::Certificate(x) => &x.fingerprint,
         }
     }
+
+    /// Has this token's `expire` timestamp already passed `now`?
+    pub fn is_expired(&self, now: TimeStamp) -> bool {
+        self.expire.map(|expire| expire <= now).unwrap_or(false)
+    }
 }
 
 impl From<&UserPassForm> for Token {
@@ -55,3 +62,17 @@ impl From<&EmailPassForm> for Token {
         Token::new(CredentialEmail::new(&x.email).into(), None, None)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::credentials::CredentialCertificate;
+
+    #[test]
+    fn certificate_credential_round_trips_to_its_fingerprint() {
+        let cert = CredentialCertificate::new("ab:cd:ef", "user1");
+        let token = Token::new(cert.into(), None, None);
+
+        assert_eq!(token.identity_id(), "ab:cd:ef");
+    }
+}
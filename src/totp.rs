@@ -0,0 +1,328 @@
+//! Time-based One-Time Password (TOTP) second factor, as specified by
+//! [RFC 6238](https://www.rfc-editor.org/rfc/rfc6238).
+//!
+//! A [Totp] stores the shared secret alongside the algorithm, digit count and period so it can
+//! live beside [crate::password::Password] as a second [crate::credentials::Credential].
+
+use std::fmt::{Display, Formatter};
+
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+use sha2::{Sha256, Sha512};
+
+use crate::errors::{PasswordError, ResultPwd};
+use crate::properties::TimeStamp;
+
+/// The HMAC algorithm backing a [Totp] secret.
+#[derive(Debug, Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub enum TotpAlgo {
+    Sha1,
+    Sha256,
+    Sha512,
+}
+
+impl Display for TotpAlgo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            TotpAlgo::Sha1 => "SHA1",
+            TotpAlgo::Sha256 => "SHA256",
+            TotpAlgo::Sha512 => "SHA512",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl TotpAlgo {
+    fn from_name(name: &str) -> ResultPwd<Self> {
+        match name.to_ascii_uppercase().as_str() {
+            "SHA1" => Ok(TotpAlgo::Sha1),
+            "SHA256" => Ok(TotpAlgo::Sha256),
+            "SHA512" => Ok(TotpAlgo::Sha512),
+            _ => Err(PasswordError::InvalidPasswordAlgo {
+                provided: name.into(),
+            }),
+        }
+    }
+
+    fn hmac(&self, secret: &[u8], counter: u64) -> Vec<u8> {
+        let msg = counter.to_be_bytes();
+        match self {
+            TotpAlgo::Sha1 => Hmac::<Sha1>::new_from_slice(secret)
+                .expect("HMAC accepts any key length")
+                .chain_update(msg)
+                .finalize()
+                .into_bytes()
+                .to_vec(),
+            TotpAlgo::Sha256 => Hmac::<Sha256>::new_from_slice(secret)
+                .expect("HMAC accepts any key length")
+                .chain_update(msg)
+                .finalize()
+                .into_bytes()
+                .to_vec(),
+            TotpAlgo::Sha512 => Hmac::<Sha512>::new_from_slice(secret)
+                .expect("HMAC accepts any key length")
+                .chain_update(msg)
+                .finalize()
+                .into_bytes()
+                .to_vec(),
+        }
+    }
+}
+
+/// The minimum and maximum number of digits a [Totp] code may have.
+pub const MIN_DIGITS: u8 = 6;
+pub const MAX_DIGITS: u8 = 8;
+
+/// The default validity period, in seconds, of a generated code.
+pub const DEFAULT_PERIOD: u64 = 30;
+
+/// A TOTP shared secret plus the parameters needed to compute and verify codes against it.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Totp {
+    secret: Vec<u8>,
+    algo: TotpAlgo,
+    digits: u8,
+    period: u64,
+    /// The last accepted step, used to reject replay of an already-consumed code.
+    last_used_step: Option<i64>,
+}
+
+impl Totp {
+    /// Create a new [Totp] from a raw (already decoded) secret.
+    pub fn new(secret: Vec<u8>, algo: TotpAlgo, digits: u8, period: u64) -> ResultPwd<Self> {
+        if !(MIN_DIGITS..=MAX_DIGITS).contains(&digits) {
+            return Err(PasswordError::InvalidPasswordAlgo {
+                provided: format!("{} digits", digits),
+            });
+        }
+
+        Ok(Totp {
+            secret,
+            algo,
+            digits,
+            period,
+            last_used_step: None,
+        })
+    }
+
+    /// Generate a new [Totp] with a random 20-byte secret (the RFC 4226 recommended size),
+    /// defaulting to [TotpAlgo::Sha1], 6 digits and a 30s period.
+    pub fn generate() -> Self {
+        use rand_core::{OsRng, RngCore};
+
+        let mut secret = vec![0u8; 20];
+        OsRng.fill_bytes(&mut secret);
+
+        Totp {
+            secret,
+            algo: TotpAlgo::Sha1,
+            digits: MIN_DIGITS,
+            period: DEFAULT_PERIOD,
+            last_used_step: None,
+        }
+    }
+
+    fn step(&self, now: TimeStamp) -> i64 {
+        now.timestamp().div_euclid(self.period as i64)
+    }
+
+    fn generate_at(&self, step: i64) -> String {
+        let hash = self.algo.hmac(&self.secret, step as u64);
+        let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+        let bytes: [u8; 4] = hash[offset..offset + 4].try_into().unwrap();
+        let code = (u32::from_be_bytes(bytes) & 0x7fff_ffff) as u64;
+        let modulus = 10u64.pow(self.digits as u32);
+        format!("{:0width$}", code % modulus, width = self.digits as usize)
+    }
+
+    /// Verify `code` against the current time, accepting a window of `skew` steps on either
+    /// side to tolerate clock drift between client and server.
+    ///
+    /// A code is rejected if it was already accepted for the same or an earlier step, so a
+    /// captured code cannot be replayed within its validity window.
+    pub fn verify(&mut self, code: &str, now: TimeStamp, skew: i64) -> ResultPwd<()> {
+        let current = self.step(now);
+
+        for delta in -skew..=skew {
+            let step = current + delta;
+
+            if let Some(last) = self.last_used_step {
+                if step <= last {
+                    continue;
+                }
+            }
+
+            if self.generate_at(step) == code {
+                self.last_used_step = Some(step);
+                return Ok(());
+            }
+        }
+
+        Err(PasswordError::InvalidPassword)
+    }
+
+    /// Encode as a PHC-like string: `$totp$algo=SHA1,digits=6,period=30$<base32 secret>`.
+    pub fn to_phc_string(&self) -> String {
+        format!(
+            "$totp$algo={},digits={},period={}${}",
+            self.algo,
+            self.digits,
+            self.period,
+            base32::encode(&self.secret)
+        )
+    }
+
+    /// Parse a PHC-like string produced by [Self::to_phc_string].
+    pub fn from_phc_string(phc: &str) -> ResultPwd<Self> {
+        let mut parts = phc.trim_start_matches('$').split('$');
+        let scheme = parts.next().unwrap_or_default();
+        if scheme != "totp" {
+            return Err(PasswordError::InvalidPasswordAlgo {
+                provided: scheme.into(),
+            });
+        }
+
+        let params = parts.next().ok_or(PasswordError::InvalidPassword)?;
+        let secret = parts.next().ok_or(PasswordError::InvalidPassword)?;
+
+        let mut algo = TotpAlgo::Sha1;
+        let mut digits = MIN_DIGITS;
+        let mut period = DEFAULT_PERIOD;
+
+        for kv in params.split(',') {
+            let (key, value) = kv.split_once('=').ok_or(PasswordError::InvalidPassword)?;
+            match key {
+                "algo" => algo = TotpAlgo::from_name(value)?,
+                "digits" => {
+                    digits = value
+                        .parse()
+                        .map_err(|_| PasswordError::InvalidPassword)?
+                }
+                "period" => {
+                    period = value
+                        .parse()
+                        .map_err(|_| PasswordError::InvalidPassword)?
+                }
+                _ => {}
+            }
+        }
+
+        Self::new(base32::decode(secret)?, algo, digits, period)
+    }
+
+    /// Build an `otpauth://totp/...` URI suitable for rendering as a QR code.
+    pub fn to_otpauth_uri(&self, label: &str, issuer: &str) -> String {
+        format!(
+            "otpauth://totp/{label}?secret={secret}&issuer={issuer}&algorithm={algo}&digits={digits}&period={period}",
+            label = label,
+            secret = base32::encode(&self.secret),
+            issuer = issuer,
+            algo = self.algo,
+            digits = self.digits,
+            period = self.period,
+        )
+    }
+}
+
+/// A tiny RFC 4648 base32 codec (no padding), kept local so this crate does not gain a
+/// dependency just to encode a handful of secret bytes.
+mod base32 {
+    use crate::errors::PasswordError;
+
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+    pub(super) fn encode(data: &[u8]) -> String {
+        let mut out = String::with_capacity((data.len() * 8).div_ceil(5));
+        let mut buffer: u32 = 0;
+        let mut bits = 0u32;
+
+        for &byte in data {
+            buffer = (buffer << 8) | byte as u32;
+            bits += 8;
+
+            while bits >= 5 {
+                bits -= 5;
+                out.push(ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+            }
+        }
+
+        if bits > 0 {
+            out.push(ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+        }
+
+        out
+    }
+
+    pub(super) fn decode(data: &str) -> Result<Vec<u8>, PasswordError> {
+        let mut out = Vec::with_capacity(data.len() * 5 / 8);
+        let mut buffer: u32 = 0;
+        let mut bits = 0u32;
+
+        for c in data.trim_end_matches('=').chars() {
+            let value = ALPHABET
+                .iter()
+                .position(|&x| x as char == c.to_ascii_uppercase())
+                .ok_or(PasswordError::InvalidPassword)? as u32;
+
+            buffer = (buffer << 5) | value;
+            bits += 5;
+
+            if bits >= 8 {
+                bits -= 8;
+                out.push((buffer >> bits) as u8);
+            }
+        }
+
+        Ok(out)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn roundtrip() {
+            let data = b"super-secret-key!!!";
+            assert_eq!(decode(&encode(data)).unwrap(), data);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rfc6238_sha1_vector() {
+        // RFC 6238 Appendix B, time = 59 -> step 1, SHA1 secret "12345678901234567890".
+        let mut totp = Totp::new(b"12345678901234567890".to_vec(), TotpAlgo::Sha1, 8, 30).unwrap();
+        let now = chrono::DateTime::from_timestamp(59, 0)
+            .unwrap()
+            .fixed_offset();
+
+        assert!(totp.verify("94287082", now, 0).is_ok());
+    }
+
+    #[test]
+    fn rejects_replay_within_window() {
+        let mut totp = Totp::new(b"12345678901234567890".to_vec(), TotpAlgo::Sha1, 8, 30).unwrap();
+        let now = chrono::DateTime::from_timestamp(59, 0)
+            .unwrap()
+            .fixed_offset();
+
+        assert!(totp.verify("94287082", now, 0).is_ok());
+        assert!(totp.verify("94287082", now, 0).is_err());
+    }
+
+    #[test]
+    fn phc_roundtrip() {
+        let totp = Totp::generate();
+        let phc = totp.to_phc_string();
+        let parsed = Totp::from_phc_string(&phc).unwrap();
+
+        assert_eq!(totp.secret, parsed.secret);
+        assert_eq!(totp.algo, parsed.algo);
+        assert_eq!(totp.digits, parsed.digits);
+        assert_eq!(totp.period, parsed.period);
+    }
+}
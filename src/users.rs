@@ -10,6 +10,15 @@ use crate::prelude::*;
 /// The default username for an admin user
 pub const USERNAME_ADMIN: &str = "admin";
 
+bitflags::bitflags! {
+    /// Administrative flags stored alongside a [User].
+    #[derive(Debug, Clone, Copy, Eq, PartialEq)]
+    pub struct UserFlag: u8 {
+        /// The account is administratively disabled and must not be allowed to log in.
+        const DISABLED = 0b0000_0001;
+    }
+}
+
 /// Represent a full user with the most common set of fields.
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct User {
@@ -20,6 +29,10 @@ pub struct User {
     password: Password,
     realm: Option<String>,
     properties: Option<HashMap<String, Properties>>,
+    flags: UserFlag,
+    /// Consecutive failed password checks, reset on a successful login. See
+    /// [crate::identity::IdentityProviderUserPwd::lockout_threshold].
+    password_failure_count: u32,
 }
 
 impl User {
@@ -38,6 +51,8 @@ impl User {
             password,
             realm: realm.map(String::from),
             properties,
+            flags: UserFlag::empty(),
+            password_failure_count: 0,
         }
     }
 
@@ -50,6 +65,25 @@ impl User {
     ) -> Self {
         Self::new(user_id, USERNAME_ADMIN, email, password, realm, properties)
     }
+
+    pub fn flags(&self) -> UserFlag {
+        self.flags
+    }
+
+    pub fn set_disabled(&mut self, disabled: bool) {
+        self.flags.set(UserFlag::DISABLED, disabled);
+    }
+
+    /// Record a failed password check, returning the new failure count.
+    pub fn record_password_failure(&mut self) -> u32 {
+        self.password_failure_count += 1;
+        self.password_failure_count
+    }
+
+    /// Reset the failure count, e.g. after a successful login.
+    pub fn reset_password_failures(&mut self) {
+        self.password_failure_count = 0;
+    }
 }
 
 impl Identity for User {
@@ -64,6 +98,18 @@ impl Identity for User {
     fn credentials(&self) -> Vec<Credential> {
         vec![CredentialUser::new(&self.user_name).into()]
     }
+
+    fn password_failure_count(&self) -> u32 {
+        self.password_failure_count
+    }
+
+    fn is_disabled(&self) -> bool {
+        self.flags.contains(UserFlag::DISABLED)
+    }
+
+    fn verify_challenge(&self, against: &str) -> ResultPwd<()> {
+        self.password.validate_password(against)
+    }
 }
 
 /// Represent an anonymous user.
@@ -81,6 +127,11 @@ impl Identity for UserAnonymous {
     fn credentials(&self) -> Vec<Credential> {
         vec![Credential::Anon(self.anon_id.clone())]
     }
+
+    /// An anonymous identity has no credential to challenge against.
+    fn verify_challenge(&self, _against: &str) -> ResultPwd<()> {
+        Err(PasswordError::InvalidPassword)
+    }
 }
 
 /// Represent a user using a username/password.
@@ -109,6 +160,10 @@ impl Identity for UserPass {
             username: self.username.clone(),
         })]
     }
+
+    fn verify_challenge(&self, against: &str) -> ResultPwd<()> {
+        self.pwd.validate_password(against)
+    }
 }
 
 /// Represent a user using an email/password.
@@ -128,4 +183,8 @@ impl Identity for EmailPass {
             email: self.email.clone(),
         })]
     }
+
+    fn verify_challenge(&self, against: &str) -> ResultPwd<()> {
+        self.pwd.validate_password(against)
+    }
 }